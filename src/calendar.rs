@@ -0,0 +1,136 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// The working hours of a day.
+#[derive(Serialize, Deserialize, Copy, Clone)]
+pub struct Hours {
+    from: NaiveTime,
+    to: NaiveTime,
+}
+
+impl Hours {
+    pub fn new(from: NaiveTime, to: NaiveTime) -> Self {
+        Self { from, to }
+    }
+
+    pub fn from(&self) -> &NaiveTime {
+        &self.from
+    }
+
+    pub fn to(&self) -> &NaiveTime {
+        &self.to
+    }
+}
+
+impl Default for Hours {
+    fn default() -> Self {
+        Self::new(
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(18, 0, 0).unwrap(),
+        )
+    }
+}
+
+/// The working calendar. Only the working time adds up to the backlog.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Calendar {
+    days: Vec<Weekday>,
+    hours: Hours,
+    holidays: Vec<NaiveDate>,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        use Weekday::*;
+        Self {
+            days: vec![Mon, Tue, Wed, Thu, Fri],
+            hours: Hours::default(),
+            holidays: vec![],
+        }
+    }
+}
+
+impl Calendar {
+    pub fn days(&self) -> &Vec<Weekday> {
+        &self.days
+    }
+
+    pub fn hours(&self) -> &Hours {
+        &self.hours
+    }
+
+    pub fn holidays(&self) -> &Vec<NaiveDate> {
+        &self.holidays
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hours.from < self.hours.to {
+            Ok(())
+        } else {
+            Err("hours must start before they end".into())
+        }
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// The working time between the given times.
+    pub fn working_time(&self, from: DateTime<Local>, to: DateTime<Local>) -> Duration {
+        let (from, to) = (from.naive_local(), to.naive_local());
+        let mut total = Duration::zero();
+        let mut date = from.date();
+
+        while date <= to.date() {
+            if self.is_working_day(date) {
+                let start = NaiveDateTime::new(date, self.hours.from).max(from);
+                let end = NaiveDateTime::new(date, self.hours.to).min(to);
+                if start < end {
+                    total += end - start;
+                }
+            }
+
+            date = match date.succ_opt() {
+                Some(date) => date,
+                None => break,
+            };
+        }
+
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    // Fri 2020-07-10 to Tue 2020-07-14, with the Monday off.
+    fn at(day: u32, hour: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(2020, 7, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn working_time() {
+        let calendar = Calendar {
+            holidays: vec![NaiveDate::from_ymd_opt(2020, 7, 13).unwrap()],
+            ..Calendar::default()
+        };
+        let hours = |from, to| calendar.working_time(from, to).num_hours();
+
+        // Within the day, and clipped to the working hours.
+        assert_eq!(hours(at(10, 10), at(10, 12)), 2);
+        assert_eq!(hours(at(10, 7), at(10, 20)), 9);
+        assert_eq!(hours(at(10, 9), at(10, 18)), 9);
+        assert_eq!(hours(at(10, 18), at(10, 23)), 0);
+
+        // Over the weekend and the holiday.
+        assert_eq!(hours(at(11, 8), at(11, 20)), 0);
+        assert_eq!(hours(at(13, 8), at(13, 20)), 0);
+        assert_eq!(hours(at(10, 18), at(14, 9)), 0);
+        assert_eq!(hours(at(10, 17), at(14, 10)), 2);
+
+        assert_eq!(hours(at(14, 10), at(10, 10)), 0);
+    }
+}
@@ -1,3 +1,6 @@
+use crate::Priority;
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+
 pub enum Command {
     Push(String),
     Pop(usize),
@@ -6,13 +9,46 @@ pub enum Command {
     Edit(usize),
     EditLast,
     EditReminders,
+    EditAll,
     Get(usize),
     GetLast,
     Remind(usize),
     RemindLast,
+    Pause(DateTime<Local>),
+    Resume,
+    Prioritize(usize, Priority),
+    PrioritizeLast(Priority),
+    Estimate(usize, Duration),
+    EstimateLast(Duration),
+    Wait(usize, String, Option<Duration>),
+    WaitLast(String, Option<Duration>),
+    Unwait(usize),
+    UnwaitLast,
+    Focus(Option<u32>),
+    Snooze(usize, Duration),
+    SnoozeLast(Duration),
+    Review,
+    Tick(usize, usize),
+    TickLast(usize),
 }
 
+// The sub commands understood by `Command::from`, for the completion.
+static NAMES: [&str; 19] = [
+    "get", "g", "pop", "p", "remind", "r", "edit", "e", "pause", "resume", "priority", "estimate",
+    "snooze", "review", "wait", "unwait", "focus", "f", "tick",
+];
+
 impl<'a> Command {
+    /// The names of the sub commands, and their aliases.
+    pub fn names() -> &'static [&'static str] {
+        &NAMES
+    }
+
+    /// Whether the command only reads, and there's nothing to save.
+    pub fn is_read_only(&self) -> bool {
+        matches!(self, Self::Get(_) | Self::GetLast)
+    }
+
     pub fn from<I>(mut statement: I) -> Option<Self>
     where
         I: Iterator<Item = &'a str>,
@@ -38,12 +74,115 @@ impl<'a> Command {
                     .next()
                     .map_or(Some(Self::EditLast), |arg| match arg {
                         "r" | "reminders" => Some(Self::EditReminders),
+                        "a" | "all" => Some(Self::EditAll),
                         arg => arg
                             .parse::<usize>()
                             .map_or(None, |num| Some(Self::Edit(num))),
                     })
             }
 
+            Some("pause") => statement.next().and_then(|arg| {
+                if let Ok(days) = arg.parse::<u32>() {
+                    Local::now()
+                        .checked_add_signed(Duration::days(days.into()))
+                        .map(Self::Pause)
+                } else {
+                    NaiveDate::parse_from_str(arg, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|date| date.and_hms_opt(0, 0, 0))
+                        .and_then(|date| Local.from_local_datetime(&date).earliest())
+                        .map(Self::Pause)
+                }
+            }),
+
+            Some("resume") => Some(Self::Resume),
+
+            Some("priority") => match (statement.next(), statement.next()) {
+                (Some(level), None) => level.parse().ok().map(Self::PrioritizeLast),
+                (Some(num), Some(level)) => num
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|num| level.parse().ok().map(|level| Self::Prioritize(num, level))),
+                _ => None,
+            },
+
+            Some("estimate") => {
+                let args: Vec<&str> = statement.collect();
+                match args.split_first() {
+                    Some((num, rest)) if !rest.is_empty() && num.parse::<usize>().is_ok() => {
+                        crate::duration::parse(&rest.join(" "))
+                            .ok()
+                            .map(|estimate| Self::Estimate(num.parse().unwrap(), estimate))
+                    }
+                    Some(_) => crate::duration::parse(&args.join(" "))
+                        .ok()
+                        .map(Self::EstimateLast),
+                    None => None,
+                }
+            }
+
+            Some("tick") => match (statement.next(), statement.next()) {
+                (Some(num), Some(item)) => match (num.parse(), item.parse()) {
+                    (Ok(num), Ok(item)) => Some(Self::Tick(num, item)),
+                    _ => None,
+                },
+                (Some(item), None) => item.parse().ok().map(Self::TickLast),
+                _ => None,
+            },
+
+            Some("snooze") => {
+                let args: Vec<&str> = statement.collect();
+                match args.split_first() {
+                    Some((num, rest)) if !rest.is_empty() && num.parse::<usize>().is_ok() => {
+                        crate::duration::parse(&rest.join(" "))
+                            .ok()
+                            .map(|duration| Self::Snooze(num.parse().unwrap(), duration))
+                    }
+                    Some(_) => crate::duration::parse(&args.join(" "))
+                        .ok()
+                        .map(Self::SnoozeLast),
+                    None => None,
+                }
+            }
+
+            Some("review") => Some(Self::Review),
+
+            Some("wait") => {
+                let mut args = statement.peekable();
+                let index = args.peek().and_then(|arg| arg.parse::<usize>().ok());
+                if index.is_some() {
+                    args.next();
+                }
+
+                let follow_up = match args.peek().and_then(|arg| arg.strip_prefix('+')) {
+                    Some(time) => {
+                        let follow_up = crate::duration::parse(time).ok()?;
+                        args.next();
+                        Some(follow_up)
+                    }
+                    None => None,
+                };
+
+                let on = args.collect::<Vec<&str>>().join(" ");
+                if on.is_empty() {
+                    None
+                } else if let Some(index) = index {
+                    Some(Self::Wait(index, on, follow_up))
+                } else {
+                    Some(Self::WaitLast(on, follow_up))
+                }
+            }
+
+            Some("unwait") => statement.next().map_or(Some(Self::UnwaitLast), |arg| {
+                arg.parse::<usize>()
+                    .map_or(None, |num| Some(Self::Unwait(num)))
+            }),
+
+            Some("f") | Some("focus") => statement.next().map_or(Some(Self::Focus(None)), |arg| {
+                arg.parse::<u32>()
+                    .map_or(None, |minutes| Some(Self::Focus(Some(minutes))))
+            }),
+
             Some(arg) => arg
                 .parse::<usize>()
                 .map_or(None, |num| Some(Self::Continue(num))),
@@ -0,0 +1,108 @@
+use crate::calendar::Calendar;
+use crate::limits::Limits;
+use crate::productivity::Measure;
+use crate::theme::Theme;
+use serde::{Deserialize, Serialize};
+
+// Use Config::examples()
+static CONFIG_EXAMPLES: &str = r###"
+# How the ages of the tasks add up to the backlog. One of:
+#
+#   formula: SumOfAges              # The ages of all the tasks added together (default)
+#   formula: MaxAge                 # The age of the oldest task
+#   formula:
+#     CountWeighted: 4h             # Sum of ages, plus 4 hours for each task
+#   formula:
+#     Exponential:
+#       doubling: 1d                # Each task counts twice as much after every day
+#
+# And the maximum backlog for each productivity level.
+
+productivity:
+  formula: SumOfAges
+  thresholds:
+    optimal: 12h
+    high: 1d
+    normal: 3d
+    low: 1w
+
+# Count only the working time towards the backlog, so that the nights,
+# weekends and holidays don't hurt the productivity.
+# Without a calendar, every hour counts.
+
+calendar:
+  days: [Mon, Tue, Wed, Thu, Fri]
+  hours:
+    from: "09:00"
+    to: "18:00"
+  holidays:
+    - 2020-12-25
+
+# Keep the stack small and fresh. A warning is shown when the stack has more
+# tasks, or older tasks than the limits. In strict mode, pushing new tasks
+# into a full stack is refused. Either way, mind exits with code 2.
+
+limits:
+  max_tasks: 10
+  max_age: 1w
+  strict: false
+
+# The colors of the tasks at each productivity level, of the ages, and of the
+# positions. They fall back to the closest ones on terminals without 24-bit
+# colors, and are turned off with NO_COLOR or --color=never.
+
+theme:
+  optimal: "#00c800"
+  high: "#7daf00"
+  normal: "#969600"
+  low: "#af7d00"
+  unproductive: "#c80000"
+  age: "#aaaaaa"
+  index: "#5f87af"
+"###;
+
+/// The user configuration. See ~/.mind/config.yml
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Config {
+    productivity: Measure,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    calendar: Option<Calendar>,
+    limits: Limits,
+    theme: Theme,
+}
+
+impl Config {
+    pub fn productivity(&self) -> &Measure {
+        &self.productivity
+    }
+
+    pub fn calendar(&self) -> &Option<Calendar> {
+        &self.calendar
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    pub fn examples() -> &'static str {
+        CONFIG_EXAMPLES
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.productivity
+            .validate()
+            .map_err(|err| format!("productivity: {}", err))?;
+
+        if let Some(calendar) = &self.calendar {
+            calendar
+                .validate()
+                .map_err(|err| format!("calendar: {}", err))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+//! Human friendly durations like `30m`, `12h`, `2d` or `1w 2d 3h`.
+
+use chrono::Duration;
+use serde::{Deserialize, Deserializer, Serializer};
+
+const UNITS: [(char, i64); 4] = [('w', 60 * 24 * 7), ('d', 60 * 24), ('h', 60), ('m', 1)];
+
+// About a hundred years.
+const MAX_WEEKS: i64 = 52 * 100;
+
+/// The longest duration, so that the dates stay in range when it's added to
+/// them, or subtracted from them, and the sums of many don't overflow.
+pub fn max() -> Duration {
+    Duration::weeks(MAX_WEEKS)
+}
+
+/// Parse a duration like `1d12h` or `1d 12h`.
+pub fn parse(text: &str) -> Result<Duration, String> {
+    let too_long = || format!("invalid duration {:?}: too long", text);
+    let mut minutes: i64 = 0;
+    let mut number = String::new();
+
+    for c in text.trim().chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+        } else if let Some((_, unit)) = UNITS.iter().find(|(u, _)| *u == c) {
+            if number.is_empty() {
+                return Err(format!(
+                    "invalid duration {:?}: missing number before {:?}",
+                    text, c
+                ));
+            }
+            // Only the digits are left, so it can only fail by overflowing.
+            minutes = number
+                .parse::<i64>()
+                .ok()
+                .and_then(|n| n.checked_mul(*unit))
+                .and_then(|m| minutes.checked_add(m))
+                .filter(|m| *m <= max().num_minutes())
+                .ok_or_else(too_long)?;
+            number.clear();
+        } else {
+            return Err(format!(
+                "invalid duration {:?}: unknown unit {:?}, use w, d, h or m",
+                text, c
+            ));
+        }
+    }
+
+    if !number.is_empty() {
+        return Err(format!(
+            "invalid duration {:?}: missing unit after {}",
+            text, number
+        ));
+    }
+
+    Duration::try_minutes(minutes).ok_or_else(too_long)
+}
+
+/// Format the duration so that it can be parsed back.
+pub fn format(duration: &Duration) -> String {
+    let mut minutes = duration.num_minutes();
+    let mut parts = Vec::new();
+
+    for (u, unit) in UNITS.iter() {
+        if minutes >= *unit {
+            parts.push(format!("{}{}", minutes / unit, u));
+            minutes %= unit;
+        }
+    }
+
+    if parts.is_empty() {
+        "0m".into()
+    } else {
+        parts.join(" ")
+    }
+}
+
+pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&format(duration))
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse(&text).map_err(serde::de::Error::custom)
+}
+
+/// Same as the parent module, for optional fields.
+pub mod option {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match duration {
+            Some(duration) => super::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| super::parse(&text).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// Same as the parent module, for lists.
+pub mod vec {
+    use chrono::Duration;
+    use serde::ser::SerializeSeq;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(durations: &[Duration], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(durations.len()))?;
+        for duration in durations {
+            seq.serialize_element(&super::format(duration))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|text| super::parse(text).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_units() {
+        assert_eq!(parse("30m"), Ok(Duration::minutes(30)));
+        assert_eq!(parse("1d12h"), Ok(Duration::hours(36)));
+        assert_eq!(
+            parse(" 1w 2d 3h "),
+            Ok(Duration::hours(7 * 24 + 2 * 24 + 3))
+        );
+        assert_eq!(parse("0m"), Ok(Duration::zero()));
+        assert!(parse("12").is_err());
+        assert!(parse("h").is_err());
+        assert!(parse("2y").is_err());
+    }
+
+    #[test]
+    fn parse_too_long() {
+        assert!(parse("99999999999999w").is_err());
+        assert!(parse("9999999999999999w").is_err());
+        assert!(parse("99999999999999999999m").is_err());
+        assert!(parse("5199w 2w").is_err());
+        assert!(parse("5201w").is_err());
+        assert!(parse("5200w").is_ok());
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let durations = [
+            Duration::zero(),
+            Duration::minutes(1),
+            Duration::minutes(59),
+            Duration::hours(36),
+            Duration::weeks(3) + Duration::days(2) + Duration::hours(1) + Duration::minutes(5),
+            max(),
+        ];
+        for duration in durations.iter() {
+            assert_eq!(parse(&format(duration)), Ok(*duration));
+        }
+        assert_eq!(format(&Duration::hours(36)), "1d 12h");
+        assert_eq!(format(&Duration::zero()), "0m");
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{render, Task};
+use chrono::{Duration, Local};
+use std::io::{self, Write};
+use std::sync::mpsc;
+use std::time;
+use termion::event::Key;
+use termion::raw::IntoRawMode;
+use termion::screen::IntoAlternateScreen;
+use termion::{clear, cursor, style, terminal_size};
+
+/// What to do with the task after a focus session.
+pub enum Outcome {
+    /// Pop it out, it's done.
+    Pop,
+    /// Keep it on the top of the stack.
+    Continue,
+    /// Push a follow-up task with the given name.
+    FollowUp(String),
+}
+
+/// The keys pressed during the session. Each key is acknowledged only when
+/// the next one is asked for, so the last one leaves nothing read behind for
+/// whatever comes after the session.
+struct Keys {
+    keys: mpsc::Receiver<io::Result<Key>>,
+    ack: mpsc::Sender<()>,
+    pending: bool,
+}
+
+impl Keys {
+    fn new() -> Self {
+        let (keys, ack) = crate::tui::keys();
+        Self {
+            keys,
+            ack,
+            pending: false,
+        }
+    }
+
+    /// The next key, if one is pressed within the timeout.
+    fn next(&mut self, timeout: time::Duration) -> Option<Key> {
+        if self.pending {
+            self.ack.send(()).ok();
+            self.pending = false;
+        }
+
+        match self.keys.recv_timeout(timeout) {
+            Ok(key) => {
+                self.pending = true;
+                key.ok()
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => None,
+            // The input is closed, like escaping everything.
+            Err(mpsc::RecvTimeoutError::Disconnected) => Some(Key::Esc),
+        }
+    }
+}
+
+/// Show a full screen countdown for the task, and ask what to do next once
+/// the time is up or the session is stopped. How long the session lasted is
+/// passed to `done` before asking.
+pub fn countdown<F>(task: &Task, duration: Duration, done: F) -> io::Result<Outcome>
+where
+    F: FnOnce(Duration) -> io::Result<()>,
+{
+    if !atty::is(atty::Stream::Stdout) {
+        return Err(io::Error::other("focus needs a terminal"));
+    }
+
+    let mut screen = io::stdout().into_raw_mode()?.into_alternate_screen()?;
+    let mut keys = Keys::new();
+    let start = Local::now();
+    let end = start + duration;
+
+    write!(screen, "{}", cursor::Hide)?;
+
+    let mut last_clock = String::new();
+    loop {
+        let left = end - Local::now();
+        if left <= Duration::zero() {
+            break;
+        }
+
+        let seconds = (left.num_milliseconds() + 999) / 1000;
+        let clock = format!("{:02}:{:02}", seconds / 60, seconds % 60);
+        if clock != last_clock {
+            draw(
+                &mut screen,
+                &[task.name(), "", &clock, "", "Press q to stop"],
+            )?;
+            last_clock = clock;
+        }
+
+        match keys.next(time::Duration::from_millis(100)) {
+            Some(Key::Char('q')) | Some(Key::Esc) | Some(Key::Ctrl('c')) => break,
+            _ => {}
+        }
+    }
+
+    let elapsed = Local::now() - start;
+    done(elapsed)?;
+
+    let summary = format!("Focused for {}", crate::duration::format(&elapsed));
+    let choices = "[p] pop  [c] continue  [f] push a follow-up";
+    draw(&mut screen, &[task.name(), "", &summary, "", choices])?;
+
+    let outcome = loop {
+        match keys.next(time::Duration::from_secs(1)) {
+            Some(Key::Char('p')) => break Outcome::Pop,
+            Some(Key::Char('c')) | Some(Key::Esc) | Some(Key::Ctrl('c')) => {
+                break Outcome::Continue
+            }
+            Some(Key::Char('f')) => {
+                let mut name = String::new();
+                let mut last_prompt = String::new();
+                loop {
+                    let prompt = format!("Follow-up: {}", name);
+                    if prompt != last_prompt {
+                        draw(&mut screen, &[task.name(), "", &summary, "", &prompt])?;
+                        last_prompt = prompt;
+                    }
+
+                    match keys.next(time::Duration::from_secs(1)) {
+                        Some(Key::Char('\n')) => break,
+                        Some(Key::Esc) | Some(Key::Ctrl('c')) => {
+                            name.clear();
+                            break;
+                        }
+                        Some(Key::Backspace) => {
+                            name.pop();
+                        }
+                        Some(Key::Char(c)) => name.push(c),
+                        _ => {}
+                    }
+                }
+
+                let name = name.trim();
+                if name.is_empty() {
+                    break Outcome::Continue;
+                } else {
+                    break Outcome::FollowUp(name.into());
+                }
+            }
+            _ => {}
+        }
+    };
+
+    write!(screen, "{}", cursor::Show)?;
+    screen.flush()?;
+    Ok(outcome)
+}
+
+fn draw<W: Write>(screen: &mut W, lines: &[&str]) -> io::Result<()> {
+    let (width, height) = terminal_size().unwrap_or((80, 24));
+    let top = (height as usize).saturating_sub(lines.len()) / 2 + 1;
+
+    write!(screen, "{}", clear::All)?;
+    for (line, row) in lines.iter().zip(top..) {
+        let col = (width as usize).saturating_sub(render::width(line)) / 2 + 1;
+        write!(
+            screen,
+            "{}{}{}{}",
+            cursor::Goto(col as u16, row as u16),
+            style::Bold,
+            line,
+            style::Reset
+        )?;
+    }
+    screen.flush()
+}
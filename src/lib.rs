@@ -1,15 +1,40 @@
+pub mod calendar;
 pub mod command;
+pub mod config;
+pub mod duration;
+pub mod focus;
+pub mod limits;
+pub mod markdown;
 pub mod mind;
+pub mod output;
 pub mod productivity;
+pub mod prompt;
 pub mod reminder;
+pub mod render;
+pub mod stats;
 pub mod storage;
 pub mod task;
+pub mod theme;
+pub mod tui;
 
+pub use crate::calendar::Calendar;
 pub use crate::command::Command;
+pub use crate::config::Config;
+pub use crate::limits::Limits;
 pub use crate::mind::Mind;
+pub use crate::productivity::Formula;
+pub use crate::productivity::Measure;
 pub use crate::productivity::Productivity;
+pub use crate::productivity::Thresholds;
 pub use crate::reminder::NthWeekday;
+pub use crate::reminder::OnDuplicate;
 pub use crate::reminder::Reminder;
 pub use crate::reminder::Repeat;
+pub use crate::stats::Sample;
+pub use crate::stats::Stats;
 pub use crate::storage::Storage;
+pub use crate::task::Priority;
+pub use crate::task::Session;
 pub use crate::task::Task;
+pub use crate::task::Waiting;
+pub use crate::theme::Theme;
@@ -0,0 +1,39 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+
+/// The work in progress limits of the stack.
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Limits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tasks: Option<usize>,
+    #[serde(
+        with = "crate::duration::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    max_age: Option<Duration>,
+    /// Refuse to push new tasks into a full stack, instead of just warning.
+    strict: bool,
+}
+
+impl Limits {
+    pub fn new(max_tasks: Option<usize>, max_age: Option<Duration>, strict: bool) -> Self {
+        Self {
+            max_tasks,
+            max_age,
+            strict,
+        }
+    }
+
+    pub fn max_tasks(&self) -> Option<usize> {
+        self.max_tasks
+    }
+
+    pub fn max_age(&self) -> &Option<Duration> {
+        &self.max_age
+    }
+
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+}
@@ -1,8 +1,16 @@
+use mind::output::{self, Format, Status};
+use mind::prompt::Prompt;
 use mind::storage::local::LocalStorage;
-use mind::{Command, Mind, Storage};
+use mind::theme::{ColorMode, Colors};
+use mind::{Command, Mind, Stats, Storage};
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
 use std::env;
-use std::io::{self, BufRead, Write};
-use termion::screen::IntoAlternateScreen;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::{mem, process, thread};
+use termion::cursor;
+use termion::screen::{IntoAlternateScreen, ToMainScreen};
 
 // Also update README.md
 static HELP: &str = r###"
@@ -11,84 +19,294 @@ mind - A productive mind
 ARGS:
   --version                       Print the binary version
   --help                          Print this help menu
+  --json                          Print the tasks, or the reminders as JSON
+  --color {auto|always|never}     Color the output only on a terminal and
+                                  without NO_COLOR set (auto), always or never
+  --format {template}             Print a line per task, or reminder, replacing
+                                  {index}, {id}, {name}, {details}, {start},
+                                  {age} and {productivity} in the template.
+                                  Reminders have {name}, {details}, {when},
+                                  {remind_at}, {repeat} and {paused}
 
 SUB COMMANDS:
-  Command        | Aliases   | Action
-  ---------------|-----------|------------------------------------------
-  {num}          |           | Continue with the task at the given position
-  pop            | p         | Pop out the current task
-  pop {num}      | p {num}   | Pop out the task at the given position
-  edit           | e         | Edit the current task
-  edit {num}     | e {num}   | Edit the task at the given position
-  edit reminders | e r       | Edit the reminders
-  get            | g         | Get details of the current task
-  get {num}      | g {num}   | Get details of the task at the given position
-  remind         | r         | Turn the current task into a reminder
-  remind {num}   | r {num}   | Turn the specified task into a reminder
+  Command                   | Aliases   | Action
+  --------------------------|-----------|------------------------------------------
+  {num}                     |           | Continue with the task at the given position
+  pop                       | p         | Pop out the current task
+  pop {num}                 | p {num}   | Pop out the task at the given position
+  edit                      | e         | Edit the current task
+  edit {num}                | e {num}   | Edit the task at the given position
+  edit reminders            | e r       | Edit the reminders
+  edit all                  | e a       | Edit the whole stack, a task per line
+  reminders                 |           | List the reminders
+  get                       | g         | Get details of the current task
+  get {num}                 | g {num}   | Get details of the task at the given position
+  remind                    | r         | Turn the current task into a reminder
+  remind {num}              | r {num}   | Turn the specified task into a reminder
+  snooze {time}             |           | Turn the current task into a reminder after the given time
+  snooze {num} {time}       |           | Turn the specified task into a reminder after the given time
+  pause {days}              |           | Pause all the reminders for the given days
+  pause {date}              |           | Pause all the reminders until the given date
+  resume                    |           | Resume the paused reminders
+  stats                     |           | Show the productivity trend
+  status                    |           | Print a one line summary of the stack
+  status {format}           |           | Same, for plain, prompt, bash, zsh, tmux, waybar or i3blocks
+  priority {level}          |           | Set the priority of the current task
+  priority {num} {level}    |           | Set the priority of the task at the given position
+  estimate {time}           |           | Set the estimated effort of the current task
+  estimate {num} {time}     |           | Set the estimated effort of the task at the given position
+  wait {what}               |           | Mark the current task as waiting on someone or something
+  wait {num} {what}         |           | Mark the task at the given position as waiting
+  wait {num} +{time} {what} |           | Same, and follow up after the given time with a reminder
+  unwait                    |           | Mark the current task as no longer waiting
+  unwait {num}              |           | Mark the task at the given position as no longer waiting
+  focus                     | f         | Focus on the current task for 25 minutes
+  focus {minutes}           | f {minutes} | Focus on the current task for the given minutes
+  review                    |           | Review the tasks one by one, oldest first
+  tui                       |           | Open the full screen interactive mode
+  tick {item}               |           | Tick, or untick, a checklist item of the current task
+  tick {num} {item}         |           | Same, for the task at the given position
 "###;
 
+/// Take out the `--color` option from the args.
+fn take_color(args: Vec<String>) -> Result<(ColorMode, Vec<String>), String> {
+    let mut color = ColorMode::Auto;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--color" {
+            color = args.next().ok_or("missing mode for --color")?.parse()?;
+        } else if let Some(mode) = arg.strip_prefix("--color=") {
+            color = mode.parse()?;
+        } else {
+            rest.push(arg);
+        }
+    }
+
+    Ok((color, rest))
+}
+
+/// Take out the output format options from the args.
+fn take_format(args: Vec<String>) -> Result<(Format, Vec<String>), String> {
+    let mut format = Format::Human;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--json" {
+            format = Format::Json;
+        } else if arg == "--format" {
+            let template = args.next().ok_or("missing template for --format")?;
+            format = Format::Template(template);
+        } else if let Some(template) = arg.strip_prefix("--format=") {
+            format = Format::Template(template.into());
+        } else {
+            rest.push(arg);
+        }
+    }
+
+    Ok((format, rest))
+}
+
+/// Print the text, through `$PAGER` if it doesn't fit in the terminal.
+fn page(text: &str) -> io::Result<()> {
+    let height = termion::terminal_size().map_or(0, |(_, height)| height as usize);
+    if !atty::is(atty::Stream::Stdout) || text.lines().count() < height {
+        println!("{}", text);
+        return Ok(());
+    }
+
+    let pager = env::var("PAGER").unwrap_or_else(|_| "less".into());
+    let mut pager = pager.split_whitespace();
+    let mut child = process::Command::new(pager.next().unwrap_or("less"))
+        .args(pager)
+        // Keep the colors, and quit if it fits after all, like git does.
+        .env("LESS", env::var("LESS").unwrap_or_else(|_| "FRX".into()))
+        .stdin(process::Stdio::piped())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager may quit before reading it all.
+        writeln!(stdin, "{}", text).ok();
+    }
+    child.wait()?;
+    Ok(())
+}
+
+/// Act on the command. Reviewing and focusing save after every step, and
+/// focusing needs a terminal, so like the full screen mode, its errors are
+/// returned.
+fn act(storage: &LocalStorage, mind: &mut Mind, command: Command) -> io::Result<()> {
+    match command {
+        Command::Review => mind.review(|mind| save(storage, mind)),
+        Command::Focus(minutes) => mind.focus(minutes, |mind| save(storage, mind)),
+        command => {
+            mind.act(command);
+            Ok(())
+        }
+    }
+}
+
+// Held while saving, so that the signals wait for the files to be written.
+static SAVING: Mutex<()> = Mutex::new(());
+
+fn save(storage: &LocalStorage, mind: &Mind) -> io::Result<()> {
+    let _saving = SAVING.lock();
+    storage.save(mind)
+}
+
+/// Exit cleanly on SIGINT, SIGTERM and SIGHUP. The interactive modes, the
+/// review and the focus sessions save after every step, so only the terminal
+/// needs to be restored, once the save in progress, if any, is done.
+fn handle_signals() -> io::Result<()> {
+    let mut signals = Signals::new([SIGINT, SIGTERM, SIGHUP])?;
+
+    let mut termios: libc::termios = unsafe { mem::zeroed() };
+    let has_termios = unsafe { libc::tcgetattr(libc::STDIN_FILENO, &mut termios) } == 0;
+
+    thread::spawn(move || {
+        if let Some(signal) = signals.forever().next() {
+            let _saving = SAVING.lock();
+
+            if has_termios {
+                unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+            }
+            if atty::is(atty::Stream::Stdout) {
+                print!("{}{}", cursor::Show, ToMainScreen);
+                io::stdout().flush().ok();
+            }
+            process::exit(128 + signal);
+        }
+    });
+    Ok(())
+}
+
 // TODO proper error handling
 fn run() -> io::Result<()> {
+    let (color, args) = take_color(env::args().skip(1).collect()).unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    });
+    let (format, args) = take_format(args).unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    });
+
+    if args.first().is_some_and(|arg| arg == "--version") {
+        println!("{}", Mind::version());
+        std::process::exit(0);
+    } else if args.first().is_some_and(|arg| arg == "--help") {
+        println!("{}", HELP);
+        std::process::exit(0);
+    }
+
+    handle_signals()?;
+
     let storage = LocalStorage::init()?;
     let mut mind = storage.load()?;
     mind.remind_tasks();
+    mind.set_colors(Colors::detect(color, atty::is(atty::Stream::Stdout)));
+
+    // Listing, getting, stats and status only read, so that they can be
+    // polled and work on read-only mounts. The due reminders will be saved
+    // by the next run that writes.
+    let mut read_only = false;
 
-    let args: Vec<String> = env::args().skip(1).collect();
     if !args.is_empty() {
-        if args.get(0).unwrap() == "--version" {
-            println!("{}", Mind::version());
+        if args.first().unwrap() == "stats" {
+            let history = storage.history()?;
+            println!(
+                "{}",
+                Stats::from(&history, mind.config().productivity().thresholds())
+            );
+            std::process::exit(0);
+        } else if args.first().unwrap() == "status" {
+            let status = args
+                .get(1)
+                .map_or(Ok(Status::Plain), |arg| arg.parse())
+                .unwrap_or_else(|err| {
+                    eprintln!("error: {}", err);
+                    std::process::exit(1);
+                });
+            // The prompts get printed on a terminal, even if captured.
+            mind.set_colors(Colors::detect(color, true));
+            println!("{}", output::status(&mind, &status));
             std::process::exit(0);
-        } else if args.get(0).unwrap() == "--help" {
-            println!("{}", HELP);
+        } else if args.first().unwrap() == "reminders" {
+            println!("{}", output::reminders(&mind, &format));
             std::process::exit(0);
+        } else if args.first().unwrap() == "tui" {
+            mind::tui::run(&mut mind, |mind| save(&storage, mind))?;
         } else if let Some(command) = Command::from(args.iter().map(|x| x.trim())) {
-            mind.act(command);
+            read_only = command.is_read_only();
+            act(&storage, &mut mind, command)?;
         } else {
             eprintln!("error: invalid sub command: {}", args.join(" "));
             std::process::exit(1);
         }
-    } else if atty::is(atty::Stream::Stdout) {
+    } else if atty::is(atty::Stream::Stdout) && matches!(format, Format::Human) {
+        let mut prompt = Prompt::new(storage.prompt_history_path())?;
         loop {
-            let mut stdout = std::io::stdout().into_alternate_screen()?;
-            let stdin = io::stdin();
-            let mut handle = stdin.lock();
+            let _screen = std::io::stdout().into_alternate_screen()?;
 
             println!("{}", &mind);
-            print!("[{}] ", mind.tasks().len());
-            stdout.flush()?;
-
-            let mut buffer = String::new();
-            handle.read_line(&mut buffer)?;
 
-            let input = buffer.trim();
-
-            if input.chars().count() == 0 {
-                break;
+            // Done on an empty line, or Ctrl-C, and the session gets saved.
+            let input = match prompt.read(&mind)? {
+                Some(input) => input,
+                None => break,
             };
 
             if input.starts_with('/') {
                 let statement = input
-                    .splitn(2, '/')
-                    .nth(1)
+                    .split_once('/')
+                    .map(|x| x.1)
                     .expect("missing command")
                     .split(' ');
 
                 if let Some(command) = Command::from(statement) {
-                    mind.act(command);
+                    act(&storage, &mut mind, command)?;
                 }
             } else {
-                mind.act(Command::Push(input.into()));
+                mind.act(Command::Push(input));
             }
+
+            // Nothing typed in the session gets lost on a crash.
+            save(&storage, &mind)?;
+            prompt.save()?;
         }
+    } else {
+        read_only = true;
     }
 
-    if let Some(focused) = mind.focused() {
-        println!("{}", &focused);
+    if let Some(index) = mind.focused_index() {
+        page(&output::task(&mind, index, &format))?;
     } else {
-        println!("{}", &mind);
+        println!("{}", output::tasks(&mind, &format));
     }
 
-    storage.save(mind)
+    for task in mind.refused() {
+        eprintln!("error: the stack is full, refused to push: {}", task.name());
+    }
+
+    let warnings = mind.warnings();
+    if !atty::is(atty::Stream::Stdout) {
+        for warning in warnings.iter() {
+            eprintln!("warning: {}", warning);
+        }
+    }
+
+    if !read_only {
+        save(&storage, &mind)?;
+        storage.record(&mind)?;
+    }
+
+    if !warnings.is_empty() {
+        // Exceeded the limits
+        std::process::exit(2);
+    }
+    Ok(())
 }
 
 fn main() {
@@ -0,0 +1,408 @@
+//! A small Markdown renderer for the details of the tasks. It knows the
+//! headings, the lists and the checkboxes, the quotes, the code and the
+//! inline styles, and wraps the lines to the given width.
+
+use crate::render;
+use termion::{color, style};
+
+#[derive(Copy, Clone, PartialEq)]
+enum Span {
+    Plain,
+    Bold,
+    Italic,
+    Code,
+    Link,
+}
+
+/// A word, in one or more differently styled pieces, like `**done**,`
+type Word = Vec<(String, Span)>;
+
+/// A list item, with its indentation, marker and text.
+struct Item<'a> {
+    indent: usize,
+    marker: String,
+    text: &'a str,
+}
+
+/// Render the Markdown text in lines of at most the given columns, with the
+/// terminal styles if styled.
+pub fn render(text: &str, width: usize, styled: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut in_code = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+        } else if in_code {
+            lines.push(code_line(line, styled));
+        } else if trimmed.is_empty() {
+            lines.push(String::new());
+        } else if is_rule(trimmed) {
+            lines.push("─".repeat(width));
+        } else if let Some((level, heading)) = heading(trimmed) {
+            let words = words(heading);
+            let span = if styled {
+                let underline = if level == 1 {
+                    style::Underline.to_string()
+                } else {
+                    String::new()
+                };
+                format!("{}{}", style::Bold, underline)
+            } else {
+                String::new()
+            };
+            lines.extend(
+                wrap(&words, width, styled)
+                    .into_iter()
+                    .map(|line| format!("{}{}{}", span, line, reset(styled))),
+            );
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            let bar = if styled {
+                format!("{}│{} ", style::Faint, style::Reset)
+            } else {
+                "│ ".into()
+            };
+            let words = words(quote.trim_start());
+            lines.extend(
+                wrap(&words, width.saturating_sub(2), styled)
+                    .into_iter()
+                    .map(|line| format!("{}{}", bar, line)),
+            );
+        } else if let Some(item) = item(line) {
+            let hanging = item.indent + render::width(&item.marker) + 1;
+            let words = words(item.text);
+            for (i, wrapped) in wrap(&words, width.saturating_sub(hanging), styled)
+                .into_iter()
+                .enumerate()
+            {
+                if i == 0 {
+                    lines.push(format!(
+                        "{}{} {}",
+                        " ".repeat(item.indent),
+                        item.marker,
+                        wrapped
+                    ));
+                } else {
+                    lines.push(format!("{}{}", " ".repeat(hanging), wrapped));
+                }
+            }
+        } else {
+            lines.extend(wrap(&words(trimmed), width, styled));
+        }
+    }
+
+    lines
+}
+
+/// The state of the checkbox at the start of the list item, if any.
+pub fn checkbox(line: &str) -> Option<bool> {
+    let text = item_text(line)?;
+    if text.starts_with("[ ] ") || text == "[ ]" {
+        Some(false)
+    } else if text.starts_with("[x] ") || text.starts_with("[X] ") || text == "[x]" || text == "[X]"
+    {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// The checkboxes of the text, as the positions of their lines and whether
+/// they're checked, skipping the code blocks.
+pub fn checklist(text: &str) -> Vec<(usize, bool)> {
+    let mut in_code = false;
+    text.lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            if line.trim_start().starts_with("```") {
+                in_code = !in_code;
+                None
+            } else if in_code {
+                None
+            } else {
+                checkbox(line).map(|checked| (i, checked))
+            }
+        })
+        .collect()
+}
+
+/// Check the checkbox of the line, or uncheck it if it's checked.
+pub fn toggle(line: &str) -> String {
+    match (item_text(line), checkbox(line)) {
+        (Some(text), Some(checked)) => {
+            let at = line.len() - text.len();
+            let mark = if checked { " " } else { "x" };
+            format!("{}[{}]{}", &line[..at], mark, &text[3..])
+        }
+        _ => line.into(),
+    }
+}
+
+fn code_line(line: &str, styled: bool) -> String {
+    if styled {
+        format!(
+            "    {}{}{}",
+            color::Fg(color::Yellow),
+            line,
+            color::Fg(color::Reset)
+        )
+    } else {
+        format!("    {}", line)
+    }
+}
+
+fn is_rule(line: &str) -> bool {
+    let line: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    line.len() >= 3
+        && (line.chars().all(|c| c == '-')
+            || line.chars().all(|c| c == '*')
+            || line.chars().all(|c| c == '_'))
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) {
+        line[level..]
+            .strip_prefix(' ')
+            .map(|text| (level, text.trim()))
+    } else {
+        None
+    }
+}
+
+/// The text of the list item, after the indentation and the marker.
+fn item_text(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if let Some(text) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| trimmed.strip_prefix(bullet))
+    {
+        return Some(text);
+    }
+
+    let digits = trimmed.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        trimmed[digits..]
+            .strip_prefix(". ")
+            .or_else(|| trimmed[digits..].strip_prefix(") "))
+    } else {
+        None
+    }
+}
+
+fn item(line: &str) -> Option<Item<'_>> {
+    let text = item_text(line)?;
+    let indent = line.len() - line.trim_start().len();
+    let trimmed = line.trim_start();
+
+    let marker = match checkbox(line) {
+        Some(false) => "☐".into(),
+        Some(true) => "☑".into(),
+        None if trimmed.starts_with(|c: char| c.is_ascii_digit()) => {
+            trimmed[..trimmed.len() - text.len()].trim_end().into()
+        }
+        None => "•".into(),
+    };
+    let text = match checkbox(line) {
+        Some(_) => text[3..].trim_start(),
+        None => text,
+    };
+
+    Some(Item {
+        indent,
+        marker,
+        text,
+    })
+}
+
+/// Split the text into the styled spans.
+fn spans(text: &str) -> Vec<(String, Span)> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let styled = if let Some(after) = rest.strip_prefix("**") {
+            after
+                .find("**")
+                .map(|end| (&after[..end], Span::Bold, &after[end + 2..]))
+        } else if c == '`' {
+            rest[1..]
+                .find('`')
+                .map(|end| (&rest[1..end + 1], Span::Code, &rest[end + 2..]))
+        } else if (c == '*' || c == '_')
+            && rest[1..].starts_with(|c: char| !c.is_whitespace())
+            && plain.chars().last().is_none_or(|c| c.is_whitespace())
+        {
+            rest[1..]
+                .find(c)
+                .map(|end| (&rest[1..end + 1], Span::Italic, &rest[end + 2..]))
+        } else if c == '[' {
+            // The url right after the label, if any, like in `[label](url)`.
+            rest.find(']')
+                .filter(|middle| rest[middle + 1..].starts_with('('))
+                .and_then(|middle| {
+                    rest[middle..].find(')').map(|end| {
+                        let end = middle + end;
+                        (&rest[..=end], Span::Link, &rest[end + 1..])
+                    })
+                })
+        } else {
+            None
+        };
+
+        match styled {
+            Some((inner, span, after)) if !inner.is_empty() => {
+                if !plain.is_empty() {
+                    spans.push((std::mem::take(&mut plain), Span::Plain));
+                }
+                if span == Span::Link {
+                    let middle = inner.find("](").expect("missing the link url");
+                    let (label, url) = (&inner[1..middle], &inner[middle + 2..inner.len() - 1]);
+                    spans.push((label.into(), Span::Link));
+                    if label != url {
+                        spans.push((format!(" ({})", url), Span::Plain));
+                    }
+                } else {
+                    spans.push((inner.into(), span));
+                }
+                rest = after;
+            }
+            _ => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() {
+        spans.push((plain, Span::Plain));
+    }
+    spans
+}
+
+fn words(text: &str) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut word: Word = Vec::new();
+
+    for (text, span) in spans(text) {
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+                continue;
+            }
+            match word.last_mut() {
+                Some((piece, last)) if *last == span => piece.push(c),
+                _ => word.push((c.to_string(), span)),
+            }
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn word_width(word: &Word) -> usize {
+    word.iter().map(|(piece, _)| render::width(piece)).sum()
+}
+
+fn paint(word: &Word, styled: bool) -> String {
+    word.iter()
+        .map(|(piece, span)| match (span, styled) {
+            (Span::Plain, _) => piece.clone(),
+            (Span::Code, false) => format!("`{}`", piece),
+            (_, false) => piece.clone(),
+            // NoBold is a double underline on some terminals, the normal
+            // intensity ends the bold everywhere.
+            (Span::Bold, true) => format!("{}{}{}", style::Bold, piece, style::NoFaint),
+            (Span::Italic, true) => format!("{}{}{}", style::Italic, piece, style::NoItalic),
+            (Span::Link, true) => format!("{}{}{}", style::Underline, piece, style::NoUnderline),
+            (Span::Code, true) => format!(
+                "{}{}{}",
+                color::Fg(color::Yellow),
+                piece,
+                color::Fg(color::Reset)
+            ),
+        })
+        .collect()
+}
+
+/// Fill the lines with the words, breaking only between the words.
+fn wrap(words: &[Word], width: usize, styled: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0;
+
+    for word in words {
+        let width_with_word = line_width + word_width(word) + (line_width > 0) as usize;
+        if line_width > 0 && width_with_word > width {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0;
+        }
+        if line_width > 0 {
+            line.push(' ');
+            line_width += 1;
+        }
+        line.push_str(&paint(word, styled));
+        line_width += word_width(word);
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+fn reset(styled: bool) -> String {
+    if styled {
+        style::Reset.to_string()
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain() {
+        let text = "# Plan\n\nSee **the** [docs](https://docs.rs) and `cargo`\nAsk [Bob] about [docs](u)\n- [ ] one\n- [x] two\n1. first\n```\nlet x = 1;\n```";
+        assert_eq!(
+            render(text, 80, false),
+            vec![
+                "Plan",
+                "",
+                "See the docs (https://docs.rs) and `cargo`",
+                "Ask [Bob] about docs (u)",
+                "☐ one",
+                "☑ two",
+                "1. first",
+                "    let x = 1;",
+            ]
+        );
+    }
+
+    #[test]
+    fn checkboxes() {
+        let text = "- [ ] one\n```\n- [ ] code\n```\n  * [x] two";
+        assert_eq!(checklist(text), vec![(0, false), (4, true)]);
+        assert_eq!(toggle("- [ ] one"), "- [x] one");
+        assert_eq!(toggle("  * [X] two"), "  * [ ] two");
+        assert_eq!(toggle("- no box"), "- no box");
+    }
+
+    #[test]
+    fn wrap_items() {
+        assert_eq!(
+            render("- one two three four", 10, false),
+            vec!["• one two", "  three", "  four"]
+        );
+    }
+}
@@ -1,15 +1,35 @@
-use crate::{Command, Productivity, Reminder, Repeat, Task};
+use crate::focus::{self, Outcome};
+use crate::render;
+use crate::theme::{Colors, Rgb};
+use crate::{Command, Config, OnDuplicate, Productivity, Reminder, Repeat, Session, Task, Waiting};
 use chrono::Duration;
-use chrono::Local;
+use chrono::{DateTime, Local};
 use chrono_humanize::HumanTime;
 use std::env;
 use std::fmt;
 use std::fs;
-use std::fs::File;
-use std::io::{self, Read, Write};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
 use std::process;
-use termion::color;
-use termion::terminal_size;
+use termion::screen::IntoAlternateScreen;
+use termion::style;
+use termion::{clear, cursor};
+
+// The names get at least this many columns, however narrow the terminal.
+static MIN_NAME_WIDTH: usize = 10;
+
+// The columns a tab takes, at most.
+static TAB_WIDTH: usize = 8;
+
+// Minutes of a focus session, unless specified.
+static FOCUS_MINUTES: u32 = 25;
+
+// The help on top of the file of `mind edit all`.
+static EDIT_ALL_HEADER: &str = "\
+# A task per line, the current one last, after its #id. Move the lines to
+# reorder, edit the names to rename, delete the lines to pop, or add lines
+# without an #id to push new tasks. If you want to cancel, just leave this
+# file empty.";
 
 // Access it using Mind::version()
 static VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -19,7 +39,12 @@ static VERSION: &str = env!("CARGO_PKG_VERSION");
 pub struct Mind {
     tasks: Vec<Task>,
     reminders: Vec<Reminder>,
+    paused_until: Option<DateTime<Local>>,
+    config: Config,
+    popped: Vec<Task>,
+    refused: Vec<Task>,
     focused: Option<usize>,
+    colors: Colors,
 }
 
 impl Mind {
@@ -27,7 +52,12 @@ impl Mind {
         Self {
             tasks,
             reminders,
+            paused_until: None,
+            config: Config::default(),
+            popped: Vec::new(),
+            refused: Vec::new(),
             focused: None,
+            colors: Colors::None,
         }
     }
 
@@ -40,13 +70,73 @@ impl Mind {
         {
             let task = self.tasks.remove(idx);
             self.tasks.push(task);
+        } else if self.is_full() && self.config.limits().strict() {
+            self.refused.push(task);
         } else {
             self.tasks.push(task);
         }
     }
 
-    fn pop(&mut self) -> Option<Task> {
-        self.tasks.pop()
+    fn is_full(&self) -> bool {
+        self.config
+            .limits()
+            .max_tasks()
+            .is_some_and(|max_tasks| self.tasks.len() >= max_tasks)
+    }
+
+    fn push_reminder(&mut self, reminder: &Reminder) {
+        let task = Task::from_reminder(reminder);
+
+        // Tasks saved by older versions don't know their reminder.
+        let existing = self.tasks.iter().position(|t| match t.reminder() {
+            Some(name) => name == reminder.name(),
+            None => t.name().trim() == task.name().trim(),
+        });
+
+        match (existing, reminder.on_duplicate()) {
+            (None, _) | (Some(_), OnDuplicate::Add) => self.tasks.push(task),
+            (Some(idx), OnDuplicate::Bump) => {
+                let task = self.tasks.remove(idx);
+                self.tasks.push(task);
+            }
+            (Some(_), OnDuplicate::Skip) => {}
+            (Some(idx), OnDuplicate::Replace) => {
+                self.tasks.remove(idx);
+                self.tasks.push(task);
+            }
+        }
+    }
+
+    fn pop(&mut self, index: usize) {
+        let task = self.tasks.remove(index);
+        self.update_follow_up(&task, None);
+        self.popped.push(task);
+    }
+
+    fn wait(&mut self, index: usize, on: Option<(String, Option<Duration>)>) {
+        let old = self.tasks[index].clone();
+        self.tasks[index].wait(on.map(|(on, follow_up)| {
+            Waiting::new(on, follow_up.map(|follow_up| Local::now() + follow_up))
+        }));
+
+        let new = self.tasks[index].clone();
+        self.update_follow_up(&old, Some(&new));
+    }
+
+    /// Replace the follow up reminder of the task with the one of its new
+    /// version, or remove it if the task is gone. A follow up that already
+    /// reminded comes back only if the follow up time changed.
+    fn update_follow_up(&mut self, old: &Task, new: Option<&Task>) {
+        let id = Some(old.id());
+        let pending = self.reminders.iter().any(|r| *r.task() == id);
+        self.reminders.retain(|r| *r.task() != id);
+
+        let follow_up = |task: &Task| task.waiting().as_ref().and_then(|w| *w.follow_up());
+        if let Some(new) = new {
+            if pending || follow_up(new) != follow_up(old) {
+                self.reminders.extend(new.follow_up_reminder());
+            }
+        }
     }
 
     /// Get the version. See ~/.mind/version
@@ -64,6 +154,90 @@ impl Mind {
         &self.reminders
     }
 
+    /// Get the configuration. See ~/.mind/config.yml
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn configure(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// Get the time until which all the reminders are paused. See ~/.mind/paused_until
+    pub fn paused_until(&self) -> &Option<DateTime<Local>> {
+        &self.paused_until
+    }
+
+    /// Pause all the reminders until the given time, or resume them.
+    pub fn pause(&mut self, until: Option<DateTime<Local>>) {
+        self.paused_until = until;
+    }
+
+    /// The colors of the output.
+    pub fn colors(&self) -> Colors {
+        self.colors
+    }
+
+    pub fn set_colors(&mut self, colors: Colors) {
+        self.colors = colors;
+    }
+
+    /// Get the tasks popped in this session
+    pub fn popped(&self) -> &Vec<Task> {
+        &self.popped
+    }
+
+    /// Get the tasks refused in this session, as the stack was full
+    pub fn refused(&self) -> &Vec<Task> {
+        &self.refused
+    }
+
+    /// Get the warnings about the exceeded limits
+    pub fn warnings(&self) -> Vec<String> {
+        let limits = self.config.limits();
+        let mut warnings = Vec::new();
+
+        if let Some(max_tasks) = limits.max_tasks() {
+            if self.tasks.len() > max_tasks {
+                warnings.push(format!(
+                    "The stack has {}, the limit is {}",
+                    count(self.tasks.len(), "task"),
+                    max_tasks
+                ));
+            }
+        }
+
+        if let Some(max_age) = limits.max_age() {
+            let now = Local::now();
+            let old = self
+                .tasks
+                .iter()
+                .filter(|t| self.age(t, now) > *max_age)
+                .count();
+            if old > 0 {
+                warnings.push(format!(
+                    "The stack has {} older than {}",
+                    count(old, "task"),
+                    crate::duration::format(max_age)
+                ));
+            }
+        }
+
+        if !self.refused.is_empty() {
+            warnings.push(format!(
+                "The stack is full, refused to push {}",
+                count(self.refused.len(), "task")
+            ));
+        }
+
+        warnings
+    }
+
+    /// Get the position of the focused task
+    pub fn focused_index(&self) -> Option<usize> {
+        self.focused.filter(|idx| *idx < self.tasks.len())
+    }
+
     /// Get the focused task
     pub fn focused(&self) -> Option<&Task> {
         self.focused
@@ -76,146 +250,244 @@ impl Mind {
         let now = Local::now();
         let mut new_reminders: Vec<Reminder> = Vec::new();
 
+        if self.paused_until.is_some_and(|until| until <= now) {
+            self.paused_until = None;
+        }
+
         for reminder in self.reminders.clone() {
-            if reminder.when() > &now {
+            if reminder.remind_at() > now {
                 new_reminders.push(reminder);
                 continue;
             }
 
-            self.push(Task::from_reminder(&reminder));
+            // With a lead time, it may remind before it's due.
+            let after = now.max(*reminder.when());
 
-            if let Some(upcoming) = reminder.upcoming(Some(now)) {
+            if self.paused_until.is_some() || reminder.paused() {
+                // Hold on to it to remind once it's resumed, or quietly move
+                // on to the upcoming occurrence if it shouldn't catch up.
+                if reminder.catch_up() {
+                    new_reminders.push(reminder);
+                } else {
+                    new_reminders.push(reminder.upcoming(Some(after)).unwrap_or(reminder));
+                }
+                continue;
+            }
+
+            self.push_reminder(&reminder);
+
+            if let Some(upcoming) = reminder.upcoming(Some(after)) {
                 new_reminders.push(upcoming);
             }
         }
         self.reminders = new_reminders;
     }
 
-    /// Total backlog
+    /// Age of the task, counting only the working time if there's a calendar
+    pub fn age(&self, task: &Task, now: DateTime<Local>) -> Duration {
+        match self.config.calendar() {
+            Some(calendar) => calendar.working_time(*task.start(), now),
+            None => now - *task.start(),
+        }
+    }
+
+    /// Total backlog, using the configured formula on the weighted ages of
+    /// the tasks that aren't waiting on something else
     pub fn backlog(&self) -> Duration {
         let now = Local::now();
-        self.tasks
-            .iter()
-            .map(|t| (now - *t.start()))
-            .fold(Duration::zero(), |x, y| x + y)
+        self.config.productivity().formula().backlog(
+            self.tasks
+                .iter()
+                .filter(|t| t.waiting().is_none())
+                .map(|t| {
+                    // Don't let a huge estimate overflow the duration.
+                    let seconds = (self.age(t, now).num_seconds() as f64 * t.weight())
+                        .min(crate::duration::max().num_seconds() as f64);
+                    Duration::seconds(seconds as i64)
+                }),
+        )
     }
 
-    /// Productivity from backlog
+    /// Productivity from backlog, using the configured thresholds
     pub fn productivity(&self) -> Productivity {
-        Productivity::from_backlog(self.backlog())
+        Productivity::from_thresholds(self.backlog(), self.config.productivity().thresholds())
     }
 
     fn edit(&mut self, index: usize) -> io::Result<()> {
-        let task = self.tasks.get_mut(index).expect("invalid index");
         let path = env::temp_dir().join("___mind___tmp_task___.md");
+        let markdown = self.tasks[index].to_markdown();
 
-        {
-            let mut file = fs::File::create(&path)?;
-            write!(file, "{}", task)?;
-        }
+        edit_file(
+            &path,
+            &markdown,
+            |content| {
+                let task = Task::from_markdown(content)?;
+                let old = std::mem::replace(&mut self.tasks[index], task.clone());
+                self.update_follow_up(&old, Some(&task));
+                Ok(())
+            },
+            // The errors go in the front matter, where the comments are
+            // ignored, replacing the ones of the previous attempt.
+            |err, content| {
+                let mut lines = content.lines().map(String::from);
+                lines
+                    .next()
+                    .into_iter()
+                    .chain(error_comments(err))
+                    .chain(lines.skip_while(|l| l.starts_with("# ")))
+                    .collect()
+            },
+        )
+    }
 
-        process::Command::new(env::var("EDITOR").unwrap_or_else(|_| "vi".into()))
-            .arg(&path)
-            .status()
-            .expect("failed to open editor");
+    /// Edit the whole stack at once, a task per line. The lines can be moved,
+    /// renamed, deleted to pop the tasks, or added to push new ones.
+    fn edit_all(&mut self) -> io::Result<()> {
+        let path = env::temp_dir().join("___mind___tmp_tasks___.txt");
+        let content: String = EDIT_ALL_HEADER
+            .lines()
+            .map(String::from)
+            .chain(
+                self.tasks
+                    .iter()
+                    .map(|task| format!("#{} {}", task.id(), task.name())),
+            )
+            .map(|line| line + "\n")
+            .collect();
 
-        let mut contents = String::new();
-        fs::File::open(&path)?.read_to_string(&mut contents)?;
-        let mut lines = contents.lines();
-        let name = lines.next().expect("missing the task name");
-        lines.next();
+        edit_file(
+            &path,
+            &content,
+            |content| {
+                let lines = parse_stack(&self.tasks, content)?;
+                self.apply_stack(lines);
+                Ok(())
+            },
+            // The errors go on top, replacing the ones of the previous
+            // attempt, followed by the header again.
+            |err, content| {
+                error_comments(err)
+                    .into_iter()
+                    .chain(EDIT_ALL_HEADER.lines().map(String::from))
+                    .chain(
+                        content
+                            .lines()
+                            .map(String::from)
+                            .skip_while(|l| l.starts_with("# ")),
+                    )
+                    .collect()
+            },
+        )
+    }
 
-        let details = lines.collect::<Vec<&str>>().join("\n");
-        let details = details.trim();
+    /// Rebuild the stack from the lines of `mind edit all`. The tasks without
+    /// a line get popped, in their original order.
+    fn apply_stack(&mut self, lines: Vec<StackLine>) {
+        let mut tasks: Vec<Option<Task>> = self.tasks.drain(..).map(Some).collect();
 
-        task.edit(
-            name.trim_start_matches("# ").trim().into(),
-            if details.chars().count() > 0 {
-                Some(details.into())
-            } else {
-                None
-            },
-        );
+        for line in lines {
+            match line {
+                StackLine::Task(index, name) => {
+                    let mut task = tasks[index].take().expect("duplicated task");
+                    if !name.is_empty() && &name != task.name() {
+                        let old = task.clone();
+                        let details = task.details().clone();
+                        task.edit(name, details);
+                        self.update_follow_up(&old, Some(&task));
+                    }
+                    self.tasks.push(task);
+                }
+                // Like any new task, unless it's already in the stack, or
+                // the stack is full.
+                StackLine::New(name) => self.push(Task::new(name)),
+            }
+        }
 
-        fs::remove_file(path)
+        for task in tasks.into_iter().flatten() {
+            self.update_follow_up(&task, None);
+            self.popped.push(task);
+        }
     }
 
     fn edit_reminders(&mut self) -> io::Result<()> {
-        // TODO: do the same for Task edit?
-
         let reminders = self.reminders();
-        let lines: Vec<String> = serde_yaml::to_string(reminders)
-            .expect("failed to encode reminders")
-            .lines()
-            .map(String::from)
+        let paused_until = self.paused_until.map(|until| {
+            format!(
+                "# All the reminders are paused until {}",
+                until.to_rfc2822()
+            )
+        });
+
+        let lines: Vec<String> = paused_until
+            .into_iter()
+            .chain(
+                serde_yaml::to_string(reminders)
+                    .expect("failed to encode reminders")
+                    .lines()
+                    .map(String::from),
+            )
             .chain(["#", "# # Examples"].iter().map(|l| l.to_string()))
             .chain(Reminder::examples().lines().map(|l| format!("# {}", l)))
             .collect();
 
         let path = env::temp_dir().join("___mind___tmp_reminders___.yml");
-        {
-            let mut file = fs::File::create(&path)?;
-            write!(file, "{}", lines.join("\n"))?;
-        }
-
-        loop {
-            process::Command::new(env::var("EDITOR").unwrap_or_else(|_| "vi".into()))
-                .arg(&path)
-                .status()
-                .expect("failed to open editor");
-
-            let mut file = File::open(&path)?;
-            let mut content = String::new();
-            file.read_to_string(&mut content)?;
 
-            if content.is_empty() {
-                break;
-            }
-
-            let probably_reminders = serde_yaml::from_str(content.trim());
-            match probably_reminders {
-                Ok(reminders) => {
-                    self.reminders = reminders;
-                    break;
-                }
-
-                Err(err) => {
-                    let updated_lines: Vec<String> = [
-                        "# There was an error in the previous attempt",
-                        "# ┌─────────────────────────────────────────",
-                        format!("{}", err)
-                            .lines()
-                            .map(|l| format!("# │ ERROR: {}", &l))
-                            .collect::<Vec<String>>()
-                            .join("\n")
-                            .trim(),
-                        "# └────────────────────────────────────────────────────────",
-                        "# If you want to cancel or quit, just leave this file empty",
-                    ]
-                    .iter()
-                    .map(|l| l.to_string())
+        edit_file(
+            &path,
+            &lines.join("\n"),
+            |content| {
+                let reminders = serde_yaml::from_str::<Vec<Reminder>>(content.trim())
+                    .map_err(|err| err.to_string())?;
+                Reminder::validate_all(&reminders)?;
+                self.reminders = reminders;
+                Ok(())
+            },
+            |err, content| {
+                error_comments(err)
+                    .into_iter()
                     .chain(
                         content
                             .lines()
                             .map(String::from)
                             .skip_while(|l| l.starts_with("# ")),
                     )
-                    .collect();
+                    .collect()
+            },
+        )
+    }
 
-                    {
-                        let mut file = fs::File::create(&path)?;
-                        write!(file, "{}", updated_lines.join("\n"))?;
-                    }
-                }
-            };
-        }
+    /// Focus on the current task for the given minutes, and log the session.
+    /// The mind gets saved once the session is logged, and once it's decided
+    /// what's next.
+    pub fn focus<F>(&mut self, minutes: Option<u32>, mut save: F) -> io::Result<()>
+    where
+        F: FnMut(&Mind) -> io::Result<()>,
+    {
+        let index = match self.tasks.len() {
+            0 => return Ok(()),
+            len => len - 1,
+        };
+
+        let start = Local::now();
+        let duration = Duration::minutes(minutes.unwrap_or(FOCUS_MINUTES).into());
+        let task = self.tasks[index].clone();
+        let outcome = focus::countdown(&task, duration, |elapsed| {
+            self.tasks[index].log_session(Session::new(start, elapsed));
+            save(self)
+        })?;
 
-        fs::remove_file(path)
+        match outcome {
+            Outcome::Pop => self.pop(index),
+            Outcome::Continue => {}
+            Outcome::FollowUp(name) => self.push(Task::new(name)),
+        }
+        save(self)
     }
 
     /// Turn the specified task into a reminder
     pub fn task_to_reminder(&mut self, index: usize) -> io::Result<()> {
         let task = self.tasks.remove(index);
+        self.update_follow_up(&task, None);
         let reminder = Reminder::new(
             task.name().clone(),
             task.details().clone(),
@@ -226,6 +498,133 @@ impl Mind {
         self.edit_reminders()
     }
 
+    /// Turn the specified task into a reminder after the given time
+    pub fn snooze(&mut self, index: usize, duration: Duration) {
+        let task = self.tasks.remove(index);
+        self.update_follow_up(&task, None);
+        let reminder = Reminder::new(
+            task.name().clone(),
+            task.details().clone(),
+            Local::now() + duration,
+            Repeat::Never,
+        );
+        self.reminders.insert(0, reminder);
+    }
+
+    /// Tick the checklist item of the specified task, and offer to pop the
+    /// task once every item is ticked.
+    pub fn tick(&mut self, index: usize, item: usize) -> io::Result<()> {
+        if !self.tasks[index].tick(item) {
+            return Ok(());
+        }
+
+        let done =
+            matches!(self.tasks[index].progress(), Some((checked, total)) if checked == total);
+        if done && atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout) {
+            print!(
+                "All done with {}, pop it out? [y/N] ",
+                self.tasks[index].name()
+            );
+            io::stdout().flush()?;
+
+            let mut buffer = String::new();
+            io::stdin().lock().read_line(&mut buffer)?;
+            if matches!(buffer.trim(), "y" | "yes") {
+                self.pop(index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Walk through the tasks, oldest first, and decide what to do with each.
+    /// The mind gets saved after every decision.
+    pub fn review<F>(&mut self, mut save: F) -> io::Result<()>
+    where
+        F: FnMut(&Mind) -> io::Result<()>,
+    {
+        // The indexes shift as the tasks are popped or moved, so keep track
+        // of the tasks by their name and start time instead.
+        let mut tasks: Vec<(String, DateTime<Local>)> = self
+            .tasks
+            .iter()
+            .map(|t| (t.name().clone(), *t.start()))
+            .collect();
+        tasks.sort_by_key(|(_, start)| *start);
+
+        let total = tasks.len();
+        let _screen = std::io::stdout().into_alternate_screen()?;
+        let stdin = io::stdin();
+        let now = Local::now();
+
+        'review: for (num, (mut name, mut start)) in tasks.into_iter().enumerate() {
+            while let Some(index) = self
+                .tasks
+                .iter()
+                .position(|t| t.name() == &name && t.start() == &start)
+            {
+                let task = &self.tasks[index];
+
+                print!("{}{}", clear::All, cursor::Goto(1, 1));
+                println!(
+                    "Reviewing {}/{}: [{}] {}",
+                    num + 1,
+                    total,
+                    index,
+                    HumanTime::from(-self.age(task, now))
+                );
+                println!();
+                println!("{}", task);
+                println!();
+                print!("[k]eep, [p]op, [s]nooze, [e]dit, move to [t]op or [q]uit: ");
+                io::stdout().flush()?;
+
+                let mut buffer = String::new();
+                if stdin.lock().read_line(&mut buffer)? == 0 {
+                    break 'review;
+                }
+
+                match buffer.trim() {
+                    "" | "k" | "keep" => break,
+                    "p" | "pop" => {
+                        self.act(Command::Pop(index));
+                        save(self)?;
+                        break;
+                    }
+                    "s" | "snooze" => {
+                        print!("Snooze for (e.g. 2d, or leave empty to edit the reminder): ");
+                        io::stdout().flush()?;
+
+                        let mut buffer = String::new();
+                        stdin.lock().read_line(&mut buffer)?;
+                        match buffer.trim() {
+                            "" => self.act(Command::Remind(index)),
+                            time => match crate::duration::parse(time) {
+                                Ok(duration) => self.act(Command::Snooze(index, duration)),
+                                Err(_) => continue,
+                            },
+                        }
+                        save(self)?;
+                        break;
+                    }
+                    "e" | "edit" => {
+                        self.act(Command::Edit(index));
+                        save(self)?;
+                        name = self.tasks[index].name().clone();
+                        start = *self.tasks[index].start();
+                    }
+                    "t" | "top" => {
+                        self.act(Command::Continue(index));
+                        save(self)?;
+                        break;
+                    }
+                    "q" | "quit" => break 'review,
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Act based on the given command.
     pub fn act(&mut self, command: Command) {
         self.focused = None;
@@ -256,108 +655,510 @@ impl Mind {
 
             Command::Pop(index) => {
                 if index < self.tasks.len() {
-                    self.tasks.remove(index);
+                    self.pop(index);
                 }
             }
 
             Command::PopLast => {
-                self.pop();
+                if !self.tasks.is_empty() {
+                    self.pop(self.tasks.len() - 1);
+                }
             }
 
             Command::Edit(index) => {
                 if index < self.tasks.len() {
-                    self.edit(index).expect("failed to edit");
+                    report(self.edit(index));
                 }
             }
 
             Command::EditLast => {
                 if !self.tasks.is_empty() {
-                    self.edit(self.tasks.len() - 1).expect("failed to edit");
+                    report(self.edit(self.tasks.len() - 1));
                 }
             }
             Command::Remind(index) => {
                 if index < self.tasks.len() {
-                    self.task_to_reminder(index).expect("failed to edit");
+                    report(self.task_to_reminder(index));
                 }
             }
 
             Command::RemindLast => {
                 if !self.tasks.is_empty() {
-                    self.task_to_reminder(self.tasks.len() - 1)
-                        .expect("failed to edit");
+                    report(self.task_to_reminder(self.tasks.len() - 1));
+                }
+            }
+
+            Command::EditReminders => report(self.edit_reminders()),
+
+            Command::EditAll => report(self.edit_all()),
+
+            Command::Prioritize(index, priority) => {
+                if let Some(task) = self.tasks.get_mut(index) {
+                    task.prioritize(priority);
+                }
+            }
+
+            Command::PrioritizeLast(priority) => {
+                if let Some(task) = self.tasks.last_mut() {
+                    task.prioritize(priority);
+                }
+            }
+
+            Command::Estimate(index, estimate) => {
+                if let Some(task) = self.tasks.get_mut(index) {
+                    task.set_estimate(Some(estimate));
+                }
+            }
+
+            Command::EstimateLast(estimate) => {
+                if let Some(task) = self.tasks.last_mut() {
+                    task.set_estimate(Some(estimate));
+                }
+            }
+
+            Command::Wait(index, on, follow_up) => {
+                if index < self.tasks.len() {
+                    self.wait(index, Some((on, follow_up)));
+                }
+            }
+
+            Command::WaitLast(on, follow_up) => {
+                if !self.tasks.is_empty() {
+                    self.wait(self.tasks.len() - 1, Some((on, follow_up)));
+                }
+            }
+
+            Command::Unwait(index) => {
+                if index < self.tasks.len() {
+                    self.wait(index, None);
+                }
+            }
+
+            Command::UnwaitLast => {
+                if !self.tasks.is_empty() {
+                    self.wait(self.tasks.len() - 1, None);
+                }
+            }
+
+            Command::Snooze(index, duration) => {
+                if index < self.tasks.len() {
+                    self.snooze(index, duration);
+                }
+            }
+
+            Command::SnoozeLast(duration) => {
+                if !self.tasks.is_empty() {
+                    self.snooze(self.tasks.len() - 1, duration);
                 }
             }
 
-            Command::EditReminders => self.edit_reminders().expect("failed to edit"),
+            // Call Mind::review and Mind::focus to save after every step.
+            Command::Review => self.review(|_| Ok(())).expect("failed to review"),
+
+            // It needs a terminal, call Mind::focus to handle the errors.
+            Command::Focus(minutes) => {
+                report(self.focus(minutes, |_| Ok(())));
+            }
+
+            Command::Tick(index, item) => {
+                if index < self.tasks.len() {
+                    self.tick(index, item).expect("failed to tick");
+                }
+            }
+
+            Command::TickLast(item) => {
+                if !self.tasks.is_empty() {
+                    self.tick(self.tasks.len() - 1, item)
+                        .expect("failed to tick");
+                }
+            }
+
+            Command::Pause(until) => self.pause(Some(until)),
+
+            Command::Resume => {
+                self.pause(None);
+                self.remind_tasks();
+            }
         }
     }
 }
 
+/// A line of `mind edit all`.
+enum StackLine {
+    /// The task at the given position, with the name on the line.
+    Task(usize, String),
+    /// A new task, with the given name.
+    New(String),
+}
+
+/// Read the lines of `mind edit all`, matching their `#id`s with the tasks,
+/// the lines without one being new tasks, and the `# ` lines comments. The
+/// unknown ids, and the ids on more lines than tasks, are errors.
+fn parse_stack(tasks: &[Task], content: &str) -> Result<Vec<StackLine>, String> {
+    let ids: Vec<String> = tasks.iter().map(Task::id).collect();
+    let mut used = vec![false; tasks.len()];
+    let mut lines = Vec::new();
+    let mut errors = Vec::new();
+
+    for line in content.lines().map(str::trim) {
+        let marked = match line.strip_prefix('#') {
+            None if line.is_empty() => continue,
+            None => {
+                lines.push(StackLine::New(line.into()));
+                continue;
+            }
+            // A comment.
+            Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => continue,
+            Some(rest) => rest,
+        };
+
+        // The tasks started at the same time share their id, so the lines
+        // take them in order.
+        let (id, name) = marked
+            .split_once(char::is_whitespace)
+            .unwrap_or((marked, ""));
+        match (0..tasks.len()).find(|&index| !used[index] && ids[index] == id) {
+            Some(index) => {
+                used[index] = true;
+                lines.push(StackLine::Task(index, name.trim().into()));
+            }
+            None if ids.iter().any(|i| i == id) => {
+                errors.push(format!("task id on more than one line: {}", line))
+            }
+            None => errors.push(format!("unknown task id: {}", line)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(lines)
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+/// Open the file with the given content in the editor, again and again until
+/// `apply` accepts what's saved, or it's left empty to cancel. After each
+/// error, the file gets the lines from `retry`, with the error shown.
+fn edit_file<A, R>(path: &Path, content: &str, mut apply: A, retry: R) -> io::Result<()>
+where
+    A: FnMut(&str) -> Result<(), String>,
+    R: Fn(&str, &str) -> Vec<String>,
+{
+    let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".into());
+    fs::write(path, content)?;
+
+    let result = loop {
+        if let Err(err) = process::Command::new(&editor).arg(path).status() {
+            break Err(io::Error::new(
+                err.kind(),
+                format!("failed to open the editor {:?}: {}", editor, err),
+            ));
+        }
+
+        let content = fs::read_to_string(path)?;
+        if content.trim().is_empty() {
+            break Ok(());
+        }
+
+        match apply(&content) {
+            Ok(()) => break Ok(()),
+            Err(err) => fs::write(path, retry(&err, &content).join("\n"))?,
+        }
+    };
+
+    fs::remove_file(path)?;
+    result
+}
+
+/// Print the error of a command, like an editor that can't be opened, as
+/// `Mind::act` can't return it.
+fn report(result: io::Result<()>) {
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+    }
+}
+
+/// The error of the previous attempt, as comments on top of the edited file.
+fn error_comments(err: &str) -> Vec<String> {
+    [
+        "# There was an error in the previous attempt",
+        "# ┌─────────────────────────────────────────",
+        err.lines()
+            .map(|l| format!("# │ ERROR: {}", &l))
+            .collect::<Vec<String>>()
+            .join("\n")
+            .trim(),
+        "# └────────────────────────────────────────────────────────",
+        "# If you want to cancel or quit, just leave this file empty",
+    ]
+    .iter()
+    .map(|l| l.to_string())
+    .collect()
+}
+
+fn count(n: usize, thing: &str) -> String {
+    if n == 1 {
+        format!("{} {}", n, thing)
+    } else {
+        format!("{} {}s", n, thing)
+    }
+}
+
 impl fmt::Display for Mind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut color = 155u8;
+        if atty::is(atty::Stream::Stdout) {
+            for warning in self.warnings() {
+                writeln!(
+                    f,
+                    "{}{}⚠ {}{}{}",
+                    style::Bold,
+                    self.colors.fg(Rgb(255, 0, 0)),
+                    warning,
+                    self.colors.reset(),
+                    style::Reset
+                )?;
+            }
+        }
+
         let len = self.tasks.len();
-        let max_name_width = terminal_size().unwrap_or((100, 0)).0 as usize - 30;
+        let idx_width = len.to_string().len();
+        let now = Local::now();
 
-        let name_width = self
+        // The age and the rest of the meta data after the names.
+        let metas: Vec<String> = self
             .tasks
             .iter()
-            .map(|t| t.name().chars().count().min(max_name_width))
-            .max()
-            .unwrap_or(0);
-        let idx_width = len.to_string().chars().count();
+            .map(|task| {
+                let due = task
+                    .due()
+                    .map(|due| format!(", due {}", HumanTime::from(due - now)))
+                    .unwrap_or_default();
+                let waiting = task
+                    .waiting()
+                    .as_ref()
+                    .map(|w| format!(", waiting on {}", w.on()))
+                    .unwrap_or_default();
+                format!(
+                    "{}{}{}",
+                    HumanTime::from(-self.age(task, now)),
+                    due,
+                    waiting
+                )
+            })
+            .collect();
 
-        let now = Local::now();
+        // The names get what's left of the line after the index, the tab and
+        // the meta data, and the longer ones get ellipsized, but never the
+        // checklist progress after them.
+        let terminal_width = render::terminal_width();
+        let meta_width = metas.iter().map(|m| render::width(m)).max().unwrap_or(0);
+        let max_name_width = terminal_width
+            .saturating_sub(idx_width + 3 + TAB_WIDTH + meta_width)
+            .max(MIN_NAME_WIDTH);
+        let names: Vec<String> = self
+            .tasks
+            .iter()
+            .map(|task| task.label(max_name_width))
+            .collect();
+        let name_width = names.iter().map(|n| render::width(n)).max().unwrap_or(0);
 
-        for (task, idx) in self.tasks.iter().zip(0..) {
-            let name = task.name().chars().take(max_name_width);
+        for (((task, meta), name), idx) in self.tasks.iter().zip(metas).zip(names).zip(0..) {
+            let name = render::pad(&name, name_width);
+            let shade = render::shade(idx, len);
+            let theme = self.config.theme();
+            let age_color = self.colors.fg(theme.age().dim(shade));
 
-            let name_color = match self.productivity() {
-                Productivity::Optimal => color::Fg(color::Rgb(0, color, 0)),
-                Productivity::High => color::Fg(color::Rgb(color - 75, color - 25, 0)),
-                Productivity::Normal => color::Fg(color::Rgb(color - 50, color - 50, 0)),
-                Productivity::Low => color::Fg(color::Rgb(color - 25, color - 75, 0)),
-                Productivity::UnProductive => color::Fg(color::Rgb(color, 0, 0)),
-            };
+            if self.colors.enabled() {
+                let name_color = if task.waiting().is_some() {
+                    // It doesn't add up to the backlog, so doesn't get the colors.
+                    format!("{}{}", style::Faint, age_color)
+                } else {
+                    self.colors
+                        .fg(theme.productivity(self.productivity()).dim(shade))
+                };
+                let (idx_color, idx_reset) = match theme.index() {
+                    Some(rgb) => (self.colors.fg(rgb), self.colors.reset()),
+                    None => (String::new(), ""),
+                };
 
-            if atty::is(atty::Stream::Stdout) {
                 write!(
                     f,
-                    "[{idx:idx_width$}] {name_color}{name:name_width$}\t{age_color}{age}{reset_color}",
+                    "{idx_color}[{idx:idx_width$}]{idx_reset} {name_color}{name}\t{age_color}{meta}{no_faint}{reset_color}",
+                    idx_color = idx_color,
                     idx = idx,
                     idx_width = idx_width,
+                    idx_reset = idx_reset,
                     name_color = name_color,
-                    name = name.collect::<String>(),
-                    age_color = color::Fg(color::Rgb(color - 50, color - 50, color - 50)),
-                    age = &HumanTime::from(*task.start() - now),
-                    reset_color = color::Fg(color::Reset),
-                    name_width = name_width
+                    name = name,
+                    age_color = age_color,
+                    meta = meta,
+                    no_faint = style::NoFaint,
+                    reset_color = self.colors.reset(),
                 )?;
             } else {
                 write!(
                     f,
-                    "[{idx:idx_width$}] {name:width$}\t{age}",
+                    "[{idx:idx_width$}] {name}\t{meta}",
                     idx = idx,
                     idx_width = idx_width,
-                    name = name.collect::<String>(),
-                    age = &HumanTime::from(*task.start() - now),
-                    width = name_width
+                    name = name,
+                    meta = meta,
                 )?;
             }
 
             if let Some(focused) = self.focused {
                 if focused == idx {
                     writeln!(f)?;
-                    write!(f, "{}", &task)?;
+                    write!(
+                        f,
+                        "{}",
+                        task.render(render::terminal_width(), self.colors.enabled())
+                    )?;
                 }
             }
 
             if idx < len - 1 {
                 writeln!(f)?
             }
-
-            color += 100u8 / len as u8;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn task(name: &str, minute: u32) -> Task {
+        let start = Local.with_ymd_and_hms(2020, 7, 10, 8, minute, 0).unwrap();
+        serde_yaml::from_str(&format!("name: {}\nstart: {}", name, start.to_rfc3339())).unwrap()
+    }
+
+    fn names(tasks: &[Task]) -> Vec<&str> {
+        tasks.iter().map(|t| t.name().as_str()).collect()
+    }
+
+    #[test]
+    fn edit_all() {
+        let tasks = vec![task("one", 1), task("two", 2), task("three", 3)];
+        let ids: Vec<String> = tasks.iter().map(Task::id).collect();
+        let mut mind = Mind::from(tasks, Vec::new());
+
+        // Moved three down, renamed one, deleted two, and added four.
+        let content = format!("# header\n#{} 3\n#{} first\n\nfour\n", ids[2], ids[0]);
+        let lines = parse_stack(mind.tasks(), &content).unwrap();
+        mind.apply_stack(lines);
+
+        assert_eq!(names(mind.tasks()), vec!["3", "first", "four"]);
+        assert_eq!(names(mind.popped()), vec!["two"]);
+        assert_eq!(mind.tasks()[0].id(), ids[2]);
+    }
+
+    #[test]
+    fn follow_up() {
+        let mut mind = Mind::from(vec![task("one", 1), task("two", 2)], Vec::new());
+        let mine = Reminder::new("Follow up on two".into(), None, Local::now(), Repeat::Never);
+        mind.reminders.push(mine);
+        let follow_ups = |mind: &Mind| {
+            mind.reminders()
+                .iter()
+                .filter(|r| r.task().is_some())
+                .map(|r| r.name().as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        };
+
+        mind.act(Command::Wait(1, "Bob".into(), Some(Duration::days(1))));
+        assert_eq!(follow_ups(&mind), "Follow up on two");
+
+        // Renamed along with the task.
+        let id = mind.tasks()[1].id();
+        let lines = parse_stack(mind.tasks(), &format!("#{} 2", id)).unwrap();
+        mind.apply_stack(lines);
+        assert_eq!(follow_ups(&mind), "Follow up on 2");
+
+        // Gone with the task, but not the reminder of the same name.
+        mind.act(Command::PopLast);
+        assert_eq!(follow_ups(&mind), "");
+        assert_eq!(mind.reminders().len(), 1);
+    }
+
+    #[test]
+    fn push_reminder() {
+        let standup = task("\"📆 Standup\"\nreminder: Standup", 1);
+        let start = *standup.start();
+        let push = |on_duplicate: &str| {
+            let reminder: Reminder = serde_yaml::from_str(&format!(
+                "name: Standup\nwhen: {}\nrepeat: EveryDay\non_duplicate: {}",
+                start.to_rfc3339(),
+                on_duplicate
+            ))
+            .unwrap();
+            let mut mind = Mind::from(vec![standup.clone(), task("other", 2)], Vec::new());
+            mind.push_reminder(&reminder);
+            mind
+        };
+
+        let mind = push("Bump");
+        assert_eq!(names(mind.tasks()), vec!["other", "📆 Standup"]);
+        assert_eq!(*mind.tasks()[1].start(), start);
+
+        let mind = push("Skip");
+        assert_eq!(names(mind.tasks()), vec!["📆 Standup", "other"]);
+
+        let mind = push("Add");
+        assert_eq!(
+            names(mind.tasks()),
+            vec!["📆 Standup", "other", "📆 Standup"]
+        );
+        assert_ne!(*mind.tasks()[2].start(), start);
+
+        let mind = push("Replace");
+        assert_eq!(names(mind.tasks()), vec!["other", "📆 Standup"]);
+        assert_ne!(*mind.tasks()[1].start(), start);
+
+        // Tasks saved without their reminder are matched by name.
+        let mut mind = Mind::from(
+            vec![task("\"📆 Standup\"", 1), task("other", 2)],
+            Vec::new(),
+        );
+        mind.push_reminder(&Reminder::new("Standup".into(), None, start, Repeat::Never));
+        assert_eq!(names(mind.tasks()), vec!["other", "📆 Standup"]);
+    }
+
+    #[test]
+    fn edit_all_errors() {
+        let tasks = vec![task("one", 1), task("two", 2)];
+        let id = tasks[0].id();
+
+        let duplicated = format!("#{} one\n#{} one again", id, id);
+        assert_eq!(
+            parse_stack(&tasks, &duplicated).err(),
+            Some(format!("task id on more than one line: #{} one again", id))
+        );
+
+        let unknown = "#0a1b2c3d4e5f lost";
+        assert_eq!(
+            parse_stack(&tasks, unknown).err(),
+            Some("unknown task id: #0a1b2c3d4e5f lost".into())
+        );
+
+        // Only the marked words are ids.
+        let lines = parse_stack(&tasks, "q3planning review\n#\n#\tnote").unwrap();
+        assert!(matches!(lines.as_slice(), [StackLine::New(name)] if name == "q3planning review"));
+    }
+
+    #[test]
+    fn edit_all_same_start() {
+        let tasks = vec![task("one", 1), task("two", 1)];
+        let id = tasks[0].id();
+        assert_eq!(tasks[1].id(), id);
+        let mut mind = Mind::from(tasks, Vec::new());
+
+        let lines = parse_stack(mind.tasks(), &format!("#{} one\n#{} 2", id, id)).unwrap();
+        mind.apply_stack(lines);
+        assert_eq!(names(mind.tasks()), vec!["one", "2"]);
+        assert!(mind.popped().is_empty());
+    }
+}
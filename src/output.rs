@@ -0,0 +1,333 @@
+//! The output of mind, human or machine readable for the scripts and the status bars.
+
+use crate::{render, Mind, Productivity, Reminder, Repeat, Task};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+// Maximum columns of the top task name in the status.
+static STATUS_NAME_WIDTH: usize = 30;
+
+/// How to print the output. See `--json` and `--format`.
+pub enum Format {
+    Human,
+    Json,
+    /// One line per item, with the `{field}`s replaced with the values.
+    Template(String),
+}
+
+/// Where the status line goes. See `mind status`.
+pub enum Status {
+    Plain,
+    /// ANSI colored, for the shell prompts that know the escapes, like fish.
+    Prompt,
+    /// Same, with the escapes marked as zero width for the bash `PS1`.
+    Bash,
+    /// Same, with the escapes marked as zero width for the zsh `PROMPT`.
+    Zsh,
+    Tmux,
+    /// JSON for the waybar custom modules, colored with Pango markup.
+    Waybar,
+    /// The full text, short text and color lines for i3blocks.
+    I3blocks,
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "prompt" => Ok(Self::Prompt),
+            "bash" => Ok(Self::Bash),
+            "zsh" => Ok(Self::Zsh),
+            "tmux" => Ok(Self::Tmux),
+            "waybar" => Ok(Self::Waybar),
+            "i3blocks" => Ok(Self::I3blocks),
+            _ => Err(format!("invalid status format: {}", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TaskView<'a> {
+    index: usize,
+    id: String,
+    name: &'a String,
+    details: &'a Option<String>,
+    start: &'a DateTime<Local>,
+    /// In seconds.
+    age: i64,
+    productivity: Productivity,
+}
+
+impl<'a> TaskView<'a> {
+    pub fn from(mind: &'a Mind, index: usize, task: &'a Task) -> Self {
+        Self {
+            index,
+            id: task.id(),
+            name: task.name(),
+            details: task.details(),
+            start: task.start(),
+            age: mind.age(task, Local::now()).num_seconds(),
+            productivity: mind.productivity(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct MindView<'a> {
+    productivity: Productivity,
+    /// In seconds.
+    backlog: i64,
+    tasks: Vec<TaskView<'a>>,
+}
+
+impl<'a> MindView<'a> {
+    pub fn from(mind: &'a Mind) -> Self {
+        Self {
+            productivity: mind.productivity(),
+            backlog: mind.backlog().num_seconds(),
+            tasks: mind
+                .tasks()
+                .iter()
+                .enumerate()
+                .map(|(index, task)| TaskView::from(mind, index, task))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ReminderView<'a> {
+    index: usize,
+    name: &'a String,
+    details: &'a Option<String>,
+    when: &'a DateTime<Local>,
+    remind_at: DateTime<Local>,
+    repeat: &'a Repeat,
+    paused: bool,
+}
+
+impl<'a> ReminderView<'a> {
+    pub fn from(mind: &Mind, index: usize, reminder: &'a Reminder) -> Self {
+        Self {
+            index,
+            name: reminder.name(),
+            details: reminder.details(),
+            when: reminder.when(),
+            remind_at: reminder.remind_at(),
+            repeat: reminder.repeat(),
+            paused: reminder.paused() || mind.paused_until().is_some(),
+        }
+    }
+}
+
+/// Replace the `{field}`s in the template with the values of the view, in one
+/// pass, so that the braces in the values are left as they are.
+pub fn render<T: Serialize>(template: &str, view: &T) -> String {
+    let fields: BTreeMap<String, serde_json::Value> =
+        serde_json::from_value(serde_json::to_value(view).expect("failed to encode"))
+            .unwrap_or_default();
+
+    let template = template.replace("\\t", "\t").replace("\\n", "\n");
+    let mut line = String::new();
+    let mut rest = template.as_str();
+
+    while let Some(open) = rest.find('{') {
+        line.push_str(&rest[..open]);
+        rest = &rest[open + 1..];
+
+        let field = rest
+            .find('}')
+            .and_then(|close| fields.get(&rest[..close]).map(|value| (close, value)));
+        match field {
+            Some((close, value)) => {
+                match value {
+                    serde_json::Value::Null => {}
+                    serde_json::Value::String(value) => line.push_str(value),
+                    value => line.push_str(&value.to_string()),
+                }
+                rest = &rest[close + 1..];
+            }
+            None => line.push('{'),
+        }
+    }
+
+    line.push_str(rest);
+    line
+}
+
+fn list<T: Serialize>(template: &str, views: &[T]) -> String {
+    views
+        .iter()
+        .map(|view| render(template, view))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The tasks in the stack.
+pub fn tasks(mind: &Mind, format: &Format) -> String {
+    match format {
+        Format::Human => mind.to_string(),
+        Format::Json => serde_json::to_string(&MindView::from(mind)).expect("failed to encode"),
+        Format::Template(template) => list(template, &MindView::from(mind).tasks),
+    }
+}
+
+/// The task at the given position.
+pub fn task(mind: &Mind, index: usize, format: &Format) -> String {
+    let task = &mind.tasks()[index];
+    match format {
+        Format::Human => task.render(render::terminal_width(), mind.colors().enabled()),
+        Format::Json => {
+            serde_json::to_string(&TaskView::from(mind, index, task)).expect("failed to encode")
+        }
+        Format::Template(template) => render(template, &TaskView::from(mind, index, task)),
+    }
+}
+
+/// The reminders.
+pub fn reminders(mind: &Mind, format: &Format) -> String {
+    let views: Vec<ReminderView> = mind
+        .reminders()
+        .iter()
+        .enumerate()
+        .map(|(index, reminder)| ReminderView::from(mind, index, reminder))
+        .collect();
+
+    match format {
+        Format::Human => {
+            let now = Local::now();
+            let paused_until = mind
+                .paused_until()
+                .map(|until| format!("All the reminders are paused until {}", until.to_rfc2822()));
+            let width = mind.reminders().len().to_string().len();
+
+            paused_until
+                .into_iter()
+                .chain(
+                    mind.reminders()
+                        .iter()
+                        .enumerate()
+                        .map(|(index, reminder)| {
+                            format!(
+                                "[{:width$}] {}\t{} ({}){}",
+                                index,
+                                reminder.name(),
+                                chrono_humanize::HumanTime::from(*reminder.when() - now),
+                                reminder.repeat(),
+                                if reminder.paused() { ", paused" } else { "" },
+                                width = width
+                            )
+                        }),
+                )
+                .collect::<Vec<String>>()
+                .join("\n")
+        }
+        Format::Json => serde_json::to_string(&views).expect("failed to encode"),
+        Format::Template(template) => list(template, &views),
+    }
+}
+
+/// A compact one line summary of the stack.
+pub fn status(mind: &Mind, status: &Status) -> String {
+    let productivity = mind.productivity();
+    let rgb = mind.config().theme().productivity(productivity);
+    let depth = mind.tasks().len();
+    let short = format!("[{}]", depth);
+    let text = match mind.tasks().last() {
+        Some(task) => format!(
+            "{} {}",
+            short,
+            render::ellipsize(task.name(), STATUS_NAME_WIDTH)
+        ),
+        None => short.clone(),
+    };
+
+    // The text in the color of the productivity, with the escapes between the
+    // given markers, for the shells to know that they take no space.
+    let colored = |text: &str, start: &str, end: &str| {
+        let colors = mind.colors();
+        if colors.enabled() {
+            let (color, reset) = (colors.fg(rgb), colors.reset());
+            format!("{start}{color}{end}{text}{start}{reset}{end}")
+        } else {
+            text.into()
+        }
+    };
+
+    match status {
+        Status::Plain => format!("{} ({:?})", text, productivity),
+        Status::Prompt => colored(&text, "", ""),
+        // Readline skips what's between these, even in a command substitution.
+        Status::Bash => colored(&text, "\x01", "\x02"),
+        Status::Zsh => colored(&text.replace('%', "%%"), "%{", "%}"),
+        Status::Tmux => format!("#[fg={}]{}#[default]", rgb.hex(), text.replace('#', "##")),
+        Status::Waybar => serde_json::json!({
+            "text": format!("<span color=\"{}\">{}</span>", rgb.hex(), markup(&text)),
+            "alt": short,
+            "tooltip": format!("{:?}", productivity),
+            "class": format!("{:?}", productivity).to_lowercase(),
+        })
+        .to_string(),
+        Status::I3blocks => format!("{}\n{}\n{}", text, short, rgb.hex()),
+    }
+}
+
+/// Escape the text for the Pango markup.
+fn markup(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_fields() {
+        let view = serde_json::json!({
+            "index": 0,
+            "name": "first",
+            "details": "see {name} and {productivity}",
+            "productivity": "High",
+            "due": null,
+        });
+        assert_eq!(
+            render("{details}|{name}", &view),
+            "see {name} and {productivity}|first"
+        );
+        assert_eq!(
+            render("[{index}]\\t{{name}} {unknown} {due}{", &view),
+            "[0]\t{first} {unknown} {"
+        );
+    }
+
+    #[test]
+    fn status_markers() {
+        let mut mind = Mind::from(vec![Task::new("50% <off> #1".into())], Vec::new());
+        mind.set_colors(crate::theme::Colors::Ansi16);
+        let green = "\x1b[32m";
+        let reset = "\x1b[39m";
+
+        assert_eq!(
+            status(&mind, &Status::Bash),
+            format!("\x01{}\x02[1] 50% <off> #1\x01{}\x02", green, reset)
+        );
+        assert_eq!(
+            status(&mind, &Status::Zsh),
+            format!("%{{{}%}}[1] 50%% <off> #1%{{{}%}}", green, reset)
+        );
+        assert_eq!(
+            status(&mind, &Status::Tmux),
+            "#[fg=#00c800][1] 50% <off> ##1#[default]"
+        );
+        assert!(status(&mind, &Status::Waybar)
+            .contains(r##""text":"<span color=\"#00c800\">[1] 50% &lt;off&gt; #1</span>""##));
+    }
+}
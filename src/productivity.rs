@@ -1,6 +1,7 @@
 use chrono::Duration;
+use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone)]
+#[derive(Serialize, Copy, Clone, Debug)]
 pub enum Productivity {
     Optimal,
     High,
@@ -10,19 +11,223 @@ pub enum Productivity {
 }
 
 impl Productivity {
+    /// Productivity from the backlog, using the default thresholds.
     pub fn from_backlog(backlog: Duration) -> Self {
-        let hours = backlog.num_hours();
+        Self::from_thresholds(backlog, &Thresholds::default())
+    }
+
+    /// The default color of the productivity level. See `Theme`.
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            Self::Optimal => (0, 200, 0),
+            Self::High => (125, 175, 0),
+            Self::Normal => (150, 150, 0),
+            Self::Low => (175, 125, 0),
+            Self::UnProductive => (200, 0, 0),
+        }
+    }
 
-        if hours <= 12 {
+    pub fn from_thresholds(backlog: Duration, thresholds: &Thresholds) -> Self {
+        if within(backlog, thresholds.optimal) {
             Self::Optimal
-        } else if hours <= 24 {
+        } else if within(backlog, thresholds.high) {
             Self::High
-        } else if hours <= 24 * 3 {
+        } else if within(backlog, thresholds.normal) {
             Self::Normal
-        } else if hours <= 24 * 7 {
+        } else if within(backlog, thresholds.low) {
             Self::Low
         } else {
             Self::UnProductive
         }
     }
 }
+
+/// Whether the backlog is within the threshold, counting in whole hours, or
+/// in whole minutes if the threshold has any, so that 12h still covers up to
+/// 12h 59m.
+fn within(backlog: Duration, threshold: Duration) -> bool {
+    let unit = if threshold.num_minutes() % 60 == 0 {
+        Duration::hours(1)
+    } else {
+        Duration::minutes(1)
+    };
+    backlog.num_seconds() / unit.num_seconds() <= threshold.num_seconds() / unit.num_seconds()
+}
+
+/// The maximum backlog for each productivity level.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Thresholds {
+    #[serde(with = "crate::duration")]
+    optimal: Duration,
+    #[serde(with = "crate::duration")]
+    high: Duration,
+    #[serde(with = "crate::duration")]
+    normal: Duration,
+    #[serde(with = "crate::duration")]
+    low: Duration,
+}
+
+impl Thresholds {
+    pub fn new(optimal: Duration, high: Duration, normal: Duration, low: Duration) -> Self {
+        Self {
+            optimal,
+            high,
+            normal,
+            low,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.optimal <= self.high && self.high <= self.normal && self.normal <= self.low {
+            Ok(())
+        } else {
+            Err("thresholds must be in the order optimal <= high <= normal <= low".into())
+        }
+    }
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self::new(
+            Duration::hours(12),
+            Duration::hours(24),
+            Duration::days(3),
+            Duration::days(7),
+        )
+    }
+}
+
+/// How the ages of the tasks add up to the backlog.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub enum Formula {
+    /// The ages of all the tasks added together.
+    #[default]
+    SumOfAges,
+    /// The age of the oldest task.
+    MaxAge,
+    /// The ages of all the tasks added together, plus the given penalty for
+    /// each task, so that many fresh tasks still count.
+    CountWeighted(#[serde(with = "crate::duration")] Duration),
+    /// Each task counts more and more as it gets older, twice as much after
+    /// each doubling time.
+    Exponential {
+        #[serde(with = "crate::duration")]
+        doubling: Duration,
+    },
+}
+
+impl Formula {
+    pub fn backlog<I>(&self, ages: I) -> Duration
+    where
+        I: Iterator<Item = Duration>,
+    {
+        match self {
+            Self::SumOfAges => ages.fold(Duration::zero(), |x, y| x + y),
+            Self::MaxAge => ages.max().unwrap_or_else(Duration::zero),
+            Self::CountWeighted(per_task) => ages.fold(Duration::zero(), |x, y| x + y + *per_task),
+            Self::Exponential { doubling } => {
+                let doubling = doubling.num_seconds().max(1) as f64;
+                let seconds: f64 = ages
+                    .map(|age| doubling * ((age.num_seconds() as f64 / doubling).exp2() - 1.0))
+                    .sum();
+
+                // Don't let a very old task overflow the duration.
+                Duration::seconds(seconds.min(crate::duration::max().num_seconds() as f64) as i64)
+            }
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::Exponential { doubling } if *doubling <= Duration::zero() => {
+                Err("Exponential doubling must be greater than 0".into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How the productivity is measured. See ~/.mind/config.yml
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Measure {
+    #[serde(with = "serde_yaml::with::singleton_map")]
+    formula: Formula,
+    thresholds: Thresholds,
+}
+
+impl Measure {
+    pub fn new(formula: Formula, thresholds: Thresholds) -> Self {
+        Self {
+            formula,
+            thresholds,
+        }
+    }
+
+    pub fn formula(&self) -> &Formula {
+        &self.formula
+    }
+
+    pub fn thresholds(&self) -> &Thresholds {
+        &self.thresholds
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        self.formula.validate()?;
+        self.thresholds.validate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_thresholds() {
+        let productivity = |backlog| format!("{:?}", Productivity::from_backlog(backlog));
+        let hm = |h, m| Duration::hours(h) + Duration::minutes(m);
+
+        assert_eq!(productivity(hm(12, 59)), "Optimal");
+        assert_eq!(productivity(hm(13, 0)), "High");
+        assert_eq!(productivity(hm(24, 59)), "High");
+        assert_eq!(productivity(hm(72, 59)), "Normal");
+        assert_eq!(productivity(hm(168, 59)), "Low");
+        assert_eq!(productivity(hm(169, 0)), "UnProductive");
+    }
+
+    #[test]
+    fn minute_thresholds() {
+        let thresholds = Thresholds::new(
+            Duration::minutes(30),
+            Duration::minutes(90),
+            Duration::hours(2),
+            Duration::hours(3),
+        );
+        let productivity = |minutes| {
+            format!(
+                "{:?}",
+                Productivity::from_thresholds(Duration::minutes(minutes), &thresholds)
+            )
+        };
+
+        assert_eq!(productivity(30), "Optimal");
+        assert_eq!(productivity(31), "High");
+        assert_eq!(productivity(90), "High");
+        assert_eq!(productivity(91), "Normal");
+        assert_eq!(productivity(179), "Normal");
+    }
+
+    #[test]
+    fn exponential() {
+        let formula = Formula::Exponential {
+            doubling: Duration::days(1),
+        };
+        let backlog = |days: &[i64]| formula.backlog(days.iter().map(|d| Duration::days(*d)));
+
+        assert_eq!(backlog(&[0]), Duration::zero());
+        assert_eq!(backlog(&[1]), Duration::days(1));
+        assert_eq!(backlog(&[2]), Duration::days(3));
+        assert_eq!(backlog(&[3, 1]), Duration::days(8));
+    }
+}
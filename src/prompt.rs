@@ -0,0 +1,113 @@
+//! The interactive prompt, with the line editing, the history and the completion.
+
+use crate::{Command, Mind};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::FileHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Completes the `/` commands, and the names of the tasks to continue with.
+#[derive(Default)]
+struct Completion {
+    names: Vec<String>,
+}
+
+impl Completer for Completion {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let typed = &line[..pos];
+
+        if let Some(command) = typed.strip_prefix('/') {
+            if command.contains(' ') {
+                return Ok((pos, Vec::new()));
+            }
+            let candidates = Command::names()
+                .iter()
+                .filter(|name| name.starts_with(command))
+                .map(|name| format!("{} ", name))
+                .collect();
+            Ok((1, candidates))
+        } else {
+            let candidates = self
+                .names
+                .iter()
+                .filter(|name| name.starts_with(typed))
+                .cloned()
+                .collect();
+            Ok((0, candidates))
+        }
+    }
+}
+
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+impl Highlighter for Completion {}
+
+impl Validator for Completion {}
+
+impl Helper for Completion {}
+
+/// Reads the lines typed in the interactive mode, and remembers them across
+/// the sessions. See ~/.mind/prompt_history
+pub struct Prompt {
+    editor: Editor<Completion, FileHistory>,
+    history_path: PathBuf,
+}
+
+impl Prompt {
+    pub fn new(history_path: &Path) -> io::Result<Self> {
+        let mut editor = Editor::new().map_err(io::Error::other)?;
+        editor.set_helper(Some(Completion::default()));
+
+        // There's no history before the first session.
+        if history_path.exists() {
+            editor
+                .load_history(history_path)
+                .map_err(io::Error::other)?;
+        }
+
+        Ok(Self {
+            editor,
+            history_path: history_path.into(),
+        })
+    }
+
+    /// Read a line, completing the names of the tasks in the mind. Returns
+    /// `None` once the user is done, with an empty line, Ctrl-C or Ctrl-D.
+    pub fn read(&mut self, mind: &Mind) -> io::Result<Option<String>> {
+        if let Some(completion) = self.editor.helper_mut() {
+            completion.names = mind.tasks().iter().map(|t| t.name().clone()).collect();
+        }
+
+        match self.editor.readline(&format!("[{}] ", mind.tasks().len())) {
+            Ok(line) if line.trim().is_empty() => Ok(None),
+            Ok(line) => {
+                self.editor
+                    .add_history_entry(line.trim())
+                    .map_err(io::Error::other)?;
+                Ok(Some(line.trim().into()))
+            }
+            Err(ReadlineError::Interrupted) | Err(ReadlineError::Eof) => Ok(None),
+            Err(err) => Err(io::Error::other(err)),
+        }
+    }
+
+    pub fn save(&mut self) -> io::Result<()> {
+        self.editor
+            .save_history(&self.history_path)
+            .map_err(io::Error::other)
+    }
+}
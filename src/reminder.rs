@@ -1,5 +1,6 @@
 use chrono::{DateTime, Datelike, Duration, Local, Weekday};
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 // Use Reminder::examples()
 static REMINDER_EXAMPLES: &str = r###"
@@ -42,6 +43,33 @@ static REMINDER_EXAMPLES: &str = r###"
     EveryNthWeekday:
       n: 2
       weekday: Sat
+
+# If the task from the previous run is still pending, a reminder can either
+# bump it to the top (Bump, default), leave it as is (Skip), add a new copy
+# (Add) or replace it with a fresh one (Replace).
+
+- name: "Test reminder every day at 9 am IST, without piling up"
+  when: "2020-07-10T09:00:00+05:30"
+  repeat: EveryDay
+  on_duplicate: Skip
+
+# A paused reminder won't remind until it's resumed. Set catch_up to false
+# to skip the occurrences missed while paused instead of reminding once.
+
+- name: "Test paused reminder every week at 11 am IST"
+  when: "2020-07-10T11:00:00+05:30"
+  repeat: EveryWeek
+  paused: true
+  catch_up: false
+
+# Remind 2 days before it's due, to get some time for preparation.
+# Supported units are w (weeks), d (days), h (hours) and m (minutes).
+
+- name: "Test reminder with a lead time, every 4 weeks at 10 am IST"
+  when: "2020-08-01T10:00:00+05:30"
+  repeat:
+    EveryNthWeek: 4
+  lead_time: 2d
 "###;
 
 #[derive(Serialize, Deserialize, Copy, Clone)]
@@ -74,13 +102,42 @@ pub enum Repeat {
     EveryNthWeekday(NthWeekday),
 }
 impl Repeat {
+    /// Check that the rule will always find a next occurrence, and that it's
+    /// not so far away that the date overflows.
+    pub fn validate(&self) -> Result<(), String> {
+        let max_weeks = crate::duration::max().num_weeks();
+        match self {
+            Self::EveryNthDay(0) => Err("EveryNthDay must be greater than 0".into()),
+            Self::EveryNthWeek(0) => Err("EveryNthWeek must be greater than 0".into()),
+            Self::EveryNthDay(days) if i64::from(*days) > max_weeks * 7 => {
+                Err(format!("EveryNthDay must be at most {}", max_weeks * 7))
+            }
+            Self::EveryNthWeek(weeks) if i64::from(*weeks) > max_weeks => {
+                Err(format!("EveryNthWeek must be at most {}", max_weeks))
+            }
+            Self::EveryNthWeekday(nthweekday) if i64::from(nthweekday.n()) >= max_weeks => {
+                Err(format!("EveryNthWeekday must be less than {}", max_weeks))
+            }
+            Self::Weekdays(weekdays) if weekdays.is_empty() => {
+                Err("Weekdays must have at least one weekday".into())
+            }
+            Self::Weekly(weekdays) if weekdays.is_empty() => {
+                Err("Weekly must have at least one weekday".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The occurrence after the given one, if any, and if it's still a date.
     fn when_next(&self, when_last: DateTime<Local>) -> Option<DateTime<Local>> {
+        let after = |days: u32| when_last.checked_add_signed(Duration::days(days.into()));
+
         match self {
             Self::Never => None,
-            Self::EveryDay => Some(when_last + Duration::days(1)),
-            Self::EveryNthDay(days) => Some(when_last + Duration::days(*days as i64)),
-            Self::EveryWeek => Some(when_last + Duration::days(7)),
-            Self::EveryNthWeek(weeks) => Some(when_last + Duration::days((weeks * 7).into())),
+            Self::EveryDay => after(1),
+            Self::EveryNthDay(days) => after(*days),
+            Self::EveryWeek => after(7),
+            Self::EveryNthWeek(weeks) => weeks.checked_mul(7).and_then(after),
             Self::Weekdays(weekdays) | Self::Weekly(weekdays) => {
                 let mut weekday = when_last.weekday().succ();
                 let mut days = 1;
@@ -90,7 +147,7 @@ impl Repeat {
                     days += 1;
                 }
 
-                Some(when_last + Duration::days(days))
+                after(days)
             }
             Self::EveryNthWeekday(nthweekday) => {
                 let mut weekday = when_last.weekday().succ();
@@ -101,29 +158,103 @@ impl Repeat {
                     days += 1;
                 }
 
-                Some(when_last + Duration::days((days + 7 * nthweekday.n()).into()))
+                nthweekday
+                    .n()
+                    .checked_mul(7)
+                    .and_then(|weeks| weeks.checked_add(days))
+                    .and_then(after)
             }
         }
     }
 
+    /// The period after which the occurrences repeat themselves.
+    fn cycle(&self) -> Option<Duration> {
+        match self {
+            Self::Never => None,
+            Self::EveryDay => Some(Duration::days(1)),
+            Self::EveryNthDay(days) => Some(Duration::days(*days as i64)),
+            Self::EveryWeek | Self::Weekdays(_) | Self::Weekly(_) => Some(Duration::weeks(1)),
+            Self::EveryNthWeek(weeks) => Some(Duration::weeks(*weeks as i64)),
+            Self::EveryNthWeekday(nthweekday) => Some(Duration::weeks(nthweekday.n() as i64 + 1)),
+        }
+    }
+
+    /// Get the first occurrence after `now`, skipping the ones that were
+    /// missed in between without visiting them one by one.
     pub fn when_upcoming(
         &self,
         when_last: DateTime<Local>,
         now: Option<DateTime<Local>>,
     ) -> Option<DateTime<Local>> {
         let now = now.unwrap_or_else(Local::now);
+        let mut when_next = self.when_next(when_last)?;
 
-        if let Some(when_next) = self.when_next(when_last) {
-            let mut when_next = when_next;
+        if when_next > now {
+            return Some(when_next);
+        }
 
-            while when_next <= now {
-                when_next = self.when_next(when_next).unwrap();
-            }
+        // Every occurrence is followed by another one a cycle later, so jump
+        // to the last occurrence before now in one go.
+        if let Some(cycle) = self.cycle().filter(|c| c.num_seconds() > 0) {
+            let cycles = (now - when_next).num_seconds() / cycle.num_seconds();
+            when_next += Duration::seconds(cycle.num_seconds() * cycles);
+        }
 
-            return Some(when_next);
+        // At most a week worth of steps remain.
+        while when_next <= now {
+            when_next = self.when_next(when_next)?;
+        }
+
+        Some(when_next)
+    }
+}
+
+impl fmt::Display for Repeat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let weekdays = |weekdays: &Vec<Weekday>| {
+            weekdays
+                .iter()
+                .map(|w| w.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
         };
 
-        None
+        match self {
+            Self::Never => write!(f, "Never"),
+            Self::EveryDay => write!(f, "EveryDay"),
+            Self::EveryNthDay(days) => write!(f, "EveryNthDay: {}", days),
+            Self::EveryWeek => write!(f, "EveryWeek"),
+            Self::EveryNthWeek(weeks) => write!(f, "EveryNthWeek: {}", weeks),
+            Self::Weekly(days) => write!(f, "Weekly: {}", weekdays(days)),
+            Self::Weekdays(days) => write!(f, "Weekdays: {}", weekdays(days)),
+            Self::EveryNthWeekday(nthweekday) => write!(
+                f,
+                "EveryNthWeekday: {} {}",
+                nthweekday.n(),
+                nthweekday.weekday()
+            ),
+        }
+    }
+}
+
+/// What to do when a reminder fires while the task from its previous run is
+/// still on the stack.
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Default)]
+pub enum OnDuplicate {
+    /// Move the existing task to the top, keeping its original start.
+    #[default]
+    Bump,
+    /// Leave the existing task where it is.
+    Skip,
+    /// Always add a new task, starting now.
+    Add,
+    /// Drop the existing task and add a new one, starting now.
+    Replace,
+}
+
+impl OnDuplicate {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
     }
 }
 
@@ -135,6 +266,32 @@ pub struct Reminder {
     when: DateTime<Local>,
     #[serde(with = "serde_yaml::with::singleton_map_recursive")]
     repeat: Repeat,
+    #[serde(default, skip_serializing_if = "OnDuplicate::is_default")]
+    on_duplicate: OnDuplicate,
+    #[serde(default, skip_serializing_if = "is_false")]
+    paused: bool,
+    #[serde(default = "default_catch_up", skip_serializing_if = "is_true")]
+    catch_up: bool,
+    #[serde(
+        default,
+        with = "crate::duration::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    lead_time: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    task: Option<String>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn default_catch_up() -> bool {
+    true
 }
 
 impl Reminder {
@@ -149,6 +306,19 @@ impl Reminder {
             details,
             when,
             repeat,
+            on_duplicate: OnDuplicate::default(),
+            paused: false,
+            catch_up: default_catch_up(),
+            lead_time: None,
+            task: None,
+        }
+    }
+
+    /// The same reminder, following up on the waiting task with the given id.
+    pub fn following_up(self, task: String) -> Self {
+        Self {
+            task: Some(task),
+            ..self
         }
     }
 
@@ -168,14 +338,202 @@ impl Reminder {
         &self.repeat
     }
 
+    pub fn on_duplicate(&self) -> OnDuplicate {
+        self.on_duplicate
+    }
+
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Whether to remind once for the occurrences missed while paused.
+    pub fn catch_up(&self) -> bool {
+        self.catch_up
+    }
+
+    /// How long before `when` it should remind.
+    pub fn lead_time(&self) -> &Option<Duration> {
+        &self.lead_time
+    }
+
+    /// When it should remind, i.e. the lead time before it's due.
+    pub fn remind_at(&self) -> DateTime<Local> {
+        self.lead_time
+            .map_or(self.when, |lead_time| self.when - lead_time)
+    }
+
+    /// The id of the waiting task it follows up on, if any.
+    pub fn task(&self) -> &Option<String> {
+        &self.task
+    }
+
     pub fn examples() -> &'static str {
         REMINDER_EXAMPLES
     }
 
+    /// Check that the reminder can be scheduled.
+    pub fn validate(&self) -> Result<(), String> {
+        self.repeat
+            .validate()
+            .map_err(|err| format!("reminder {:?}: {}", self.name, err))
+    }
+
+    /// Validate all the reminders, reporting every invalid one.
+    pub fn validate_all(reminders: &[Self]) -> Result<(), String> {
+        let errors: Vec<String> = reminders
+            .iter()
+            .filter_map(|r| r.validate().err())
+            .collect();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
     pub fn upcoming(&self, now: Option<DateTime<Local>>) -> Option<Self> {
-        self.repeat.when_upcoming(self.when, now).map(|when| {
-            let (name, details) = (self.name.clone(), self.details.clone());
-            Self::new(name, details, when, self.repeat.clone())
+        self.repeat.when_upcoming(self.when, now).map(|when| Self {
+            when,
+            ..self.clone()
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn when_upcoming_stepping(
+        repeat: &Repeat,
+        when_last: DateTime<Local>,
+        now: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        let mut when_next = repeat.when_next(when_last)?;
+        while when_next <= now {
+            when_next = repeat.when_next(when_next).unwrap();
+        }
+        Some(when_next)
+    }
+
+    fn repeats() -> Vec<Repeat> {
+        use Weekday::*;
+        vec![
+            Repeat::Never,
+            Repeat::EveryDay,
+            Repeat::EveryNthDay(1),
+            Repeat::EveryNthDay(3),
+            Repeat::EveryNthDay(45),
+            Repeat::EveryWeek,
+            Repeat::EveryNthWeek(1),
+            Repeat::EveryNthWeek(3),
+            Repeat::Weekdays(vec![Sat, Sun]),
+            Repeat::Weekdays(vec![Mon, Tue, Wed, Thu, Fri]),
+            Repeat::Weekly(vec![Wed]),
+            Repeat::EveryNthWeekday(NthWeekday::from(0, Mon)),
+            Repeat::EveryNthWeekday(NthWeekday::from(2, Sat)),
+        ]
+    }
+
+    #[test]
+    fn test_when_upcoming_matches_stepping() {
+        let when_last = Local.with_ymd_and_hms(2020, 7, 10, 8, 0, 0).unwrap();
+        let nows = [
+            when_last - Duration::days(3),
+            when_last,
+            when_last + Duration::hours(5),
+            when_last + Duration::days(1),
+            when_last + Duration::days(1) + Duration::milliseconds(1),
+            when_last + Duration::days(7) - Duration::seconds(1),
+            when_last + Duration::days(100) + Duration::minutes(17),
+            when_last + Duration::days(365 * 3) + Duration::hours(13),
+            when_last + Duration::days(365 * 6),
+        ];
+
+        for repeat in repeats() {
+            for now in nows.iter() {
+                assert_eq!(
+                    repeat.when_upcoming(when_last, Some(*now)),
+                    when_upcoming_stepping(&repeat, when_last, *now),
+                    "{:?} at {}",
+                    serde_yaml::to_string(&repeat).unwrap(),
+                    now,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_when_upcoming_is_after_now() {
+        let when_last = Local.with_ymd_and_hms(2001, 1, 1, 22, 30, 0).unwrap();
+        let now = Local.with_ymd_and_hms(2020, 7, 10, 22, 30, 0).unwrap();
+
+        for repeat in repeats() {
+            if let Some(when) = repeat.when_upcoming(when_last, Some(now)) {
+                assert!(when > now);
+                assert!(when - now <= repeat.cycle().unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        for repeat in repeats() {
+            assert_eq!(repeat.validate(), Ok(()));
+        }
+
+        let invalid = [
+            (Repeat::EveryNthDay(0), "EveryNthDay must be greater than 0"),
+            (
+                Repeat::EveryNthWeek(0),
+                "EveryNthWeek must be greater than 0",
+            ),
+            (
+                Repeat::Weekdays(Vec::new()),
+                "Weekdays must have at least one weekday",
+            ),
+            (
+                Repeat::Weekly(Vec::new()),
+                "Weekly must have at least one weekday",
+            ),
+            (
+                Repeat::EveryNthDay(u32::MAX),
+                "EveryNthDay must be at most 36400",
+            ),
+            (
+                Repeat::EveryNthWeek(700_000_000),
+                "EveryNthWeek must be at most 5200",
+            ),
+            (
+                Repeat::EveryNthWeekday(NthWeekday::from(u32::MAX, Weekday::Mon)),
+                "EveryNthWeekday must be less than 5200",
+            ),
+        ];
+        for (repeat, err) in invalid.iter() {
+            assert_eq!(repeat.validate(), Err(err.to_string()));
+        }
+
+        // Past the last date, there's no next occurrence, but no overflow.
+        let last = DateTime::<Local>::MAX_UTC.with_timezone(&Local);
+        assert!(Repeat::EveryNthDay(36400).when_next(last).is_none());
+        assert!(Repeat::EveryNthWeek(u32::MAX).when_next(last).is_none());
+
+        let reminders = vec![
+            Reminder::new("fine".into(), None, Local::now(), Repeat::EveryDay),
+            Reminder::new("never".into(), None, Local::now(), Repeat::EveryNthDay(0)),
+            Reminder::new(
+                "nobody".into(),
+                None,
+                Local::now(),
+                Repeat::Weekly(Vec::new()),
+            ),
+        ];
+        assert_eq!(
+            Reminder::validate_all(&reminders),
+            Err("reminder \"never\": EveryNthDay must be greater than 0\n\
+                 reminder \"nobody\": Weekly must have at least one weekday"
+                .into())
+        );
+    }
+}
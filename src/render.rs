@@ -0,0 +1,81 @@
+//! Text layout for the terminal, measured in display columns rather than
+//! chars, so that the emojis and the CJK text line up.
+
+use termion::terminal_size;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+// The darkest and the brightest shades of the task colors.
+static SHADE_FROM: u8 = 155;
+static SHADE_TO: u8 = 255;
+
+/// The columns of the terminal, or 80 when not on a terminal.
+pub fn terminal_width() -> usize {
+    terminal_size().map_or(80, |(width, _)| width as usize)
+}
+
+/// The columns taken by the text on the terminal.
+pub fn width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Cut the text to fit in the given columns, ending with `…` when cut.
+pub fn ellipsize(text: &str, columns: usize) -> String {
+    if width(text) <= columns {
+        return text.into();
+    }
+    if columns == 0 {
+        return String::new();
+    }
+
+    let mut cut = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let w = c.width().unwrap_or(0);
+        if used + w > columns - 1 {
+            break;
+        }
+        cut.push(c);
+        used += w;
+    }
+    cut.push('…');
+    cut
+}
+
+/// Fill the text with spaces up to the given columns.
+pub fn pad(text: &str, columns: usize) -> String {
+    format!(
+        "{}{}",
+        text,
+        " ".repeat(columns.saturating_sub(width(text)))
+    )
+}
+
+/// The shade of the item at the given position, from dark to bright over
+/// any number of items.
+pub fn shade(index: usize, count: usize) -> u8 {
+    let steps = count.saturating_sub(1).max(1);
+    let range = (SHADE_TO - SHADE_FROM) as usize;
+    SHADE_FROM + (range * index.min(steps) / steps) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ellipsize_by_columns() {
+        assert_eq!(ellipsize("hello", 10), "hello");
+        assert_eq!(ellipsize("hello", 4), "hel…");
+        assert_eq!(ellipsize("hello", 0), "");
+        assert_eq!(ellipsize("日本語のタスク", 7), "日本語…");
+        assert_eq!(width(&ellipsize("📆 meeting", 5)), 5);
+    }
+
+    #[test]
+    fn shade_any_count() {
+        assert_eq!(shade(0, 1), SHADE_FROM);
+        assert_eq!(shade(0, 500), SHADE_FROM);
+        assert_eq!(shade(499, 500), SHADE_TO);
+        assert_eq!(shade(1, 2), SHADE_TO);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::{Mind, Productivity, Thresholds};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+static SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// A snapshot of the mind, taken on every run. See ~/.mind/history.yml
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Sample {
+    time: DateTime<Local>,
+    depth: usize,
+    #[serde(with = "crate::duration")]
+    backlog: Duration,
+    /// The ages of the tasks popped since the previous sample.
+    #[serde(
+        default,
+        with = "crate::duration::vec",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    popped: Vec<Duration>,
+}
+
+impl Sample {
+    pub fn from(mind: &Mind) -> Self {
+        let time = Local::now();
+        Self {
+            time,
+            depth: mind.tasks().len(),
+            backlog: mind.backlog(),
+            popped: mind.popped().iter().map(|t| time - *t.start()).collect(),
+        }
+    }
+
+    pub fn time(&self) -> &DateTime<Local> {
+        &self.time
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn backlog(&self) -> &Duration {
+        &self.backlog
+    }
+
+    /// The ages of the tasks popped since the previous sample.
+    pub fn popped(&self) -> &Vec<Duration> {
+        &self.popped
+    }
+}
+
+/// The averages of the samples taken in a day.
+pub struct Day {
+    date: NaiveDate,
+    depth: f64,
+    backlog: Duration,
+    productivity: Productivity,
+}
+
+impl Day {
+    pub fn date(&self) -> &NaiveDate {
+        &self.date
+    }
+
+    pub fn depth(&self) -> f64 {
+        self.depth
+    }
+
+    pub fn backlog(&self) -> &Duration {
+        &self.backlog
+    }
+
+    pub fn productivity(&self) -> Productivity {
+        self.productivity
+    }
+}
+
+/// The productivity trend. Run `mind stats`
+pub struct Stats {
+    days: Vec<Day>,
+    depth: f64,
+    time_to_pop: Option<Duration>,
+}
+
+impl Stats {
+    pub fn from(samples: &[Sample], thresholds: &Thresholds) -> Self {
+        let mut by_date: BTreeMap<NaiveDate, Vec<&Sample>> = BTreeMap::new();
+        for sample in samples {
+            by_date
+                .entry(sample.time.date_naive())
+                .or_default()
+                .push(sample);
+        }
+
+        let days = by_date
+            .into_iter()
+            .map(|(date, samples)| {
+                let count = samples.len() as i32;
+                let backlog = samples.iter().fold(Duration::zero(), |x, s| x + s.backlog) / count;
+                Day {
+                    date,
+                    depth: samples.iter().map(|s| s.depth as f64).sum::<f64>() / count as f64,
+                    backlog,
+                    productivity: Productivity::from_thresholds(backlog, thresholds),
+                }
+            })
+            .collect();
+
+        let depth = if samples.is_empty() {
+            0.0
+        } else {
+            samples.iter().map(|s| s.depth as f64).sum::<f64>() / samples.len() as f64
+        };
+
+        let popped: Vec<&Duration> = samples.iter().flat_map(|s| s.popped.iter()).collect();
+        let time_to_pop = if popped.is_empty() {
+            None
+        } else {
+            Some(popped.iter().fold(Duration::zero(), |x, y| x + **y) / popped.len() as i32)
+        };
+
+        Self {
+            days,
+            depth,
+            time_to_pop,
+        }
+    }
+
+    /// Daily averages, oldest first.
+    pub fn days(&self) -> &Vec<Day> {
+        &self.days
+    }
+
+    /// The day with the least backlog.
+    pub fn best_day(&self) -> Option<&Day> {
+        self.days.iter().min_by_key(|d| d.backlog)
+    }
+
+    /// The day with the most backlog.
+    pub fn worst_day(&self) -> Option<&Day> {
+        self.days.iter().max_by_key(|d| d.backlog)
+    }
+
+    /// Average number of tasks in the stack.
+    pub fn depth(&self) -> f64 {
+        self.depth
+    }
+
+    /// Average age of the tasks when they were popped.
+    pub fn time_to_pop(&self) -> &Option<Duration> {
+        &self.time_to_pop
+    }
+
+    /// The daily backlog trend as a sparkline.
+    pub fn sparkline(&self) -> String {
+        let max = self
+            .days
+            .iter()
+            .map(|d| d.backlog.num_seconds())
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        self.days
+            .iter()
+            .map(|d| {
+                let level = d.backlog.num_seconds().max(0) * (SPARKS.len() as i64 - 1) / max;
+                SPARKS[level as usize]
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.days.is_empty() {
+            return write!(f, "No history yet...");
+        }
+
+        writeln!(f, "Backlog trend: {}", self.sparkline())?;
+        writeln!(f)?;
+
+        writeln!(
+            f,
+            "{:10}  {:>5}  {:>12}  Productivity",
+            "Day", "Depth", "Backlog"
+        )?;
+        for day in self.days.iter() {
+            writeln!(
+                f,
+                "{:10}  {:>5.1}  {:>12}  {:?}",
+                day.date.to_string(),
+                day.depth,
+                crate::duration::format(&day.backlog),
+                day.productivity,
+            )?;
+        }
+        writeln!(f)?;
+
+        if let (Some(best), Some(worst)) = (self.best_day(), self.worst_day()) {
+            writeln!(
+                f,
+                "Best day:            {} ({:?})",
+                best.date, best.productivity
+            )?;
+            writeln!(
+                f,
+                "Worst day:           {} ({:?})",
+                worst.date, worst.productivity
+            )?;
+        }
+        write!(f, "Average stack depth: {:.1}", self.depth)?;
+
+        if let Some(time_to_pop) = self.time_to_pop {
+            writeln!(f)?;
+            write!(
+                f,
+                "Average time to pop: {}",
+                crate::duration::format(&time_to_pop)
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,12 +1,43 @@
-use crate::{Mind, Reminder, Storage};
+use crate::{Config, Mind, Reminder, Sample, Storage};
+use chrono::{DateTime, Local};
+use std::cell::Cell;
 use std::fs;
-use std::fs::File;
+use std::fs::{File, OpenOptions};
 use std::io::{self, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Write the file only if the content changed, so that the unchanged files
+/// aren't touched, and read-only mounts keep working. The content goes to a
+/// temporary file first, so that a crash never leaves a half written file.
+/// A symlinked file gets written where it points to, and stays a symlink.
+fn write_if_changed(path: &Path, content: &str) -> io::Result<()> {
+    if fs::read_to_string(path).map_or(true, |old| old != content) {
+        let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, &path)?;
+    }
+    Ok(())
+}
 
 pub struct LocalStorage {
     mind_tasks_path: PathBuf,
     mind_reminders_path: PathBuf,
+    mind_paused_until_path: PathBuf,
+    mind_config_path: PathBuf,
+    mind_history_path: PathBuf,
+    mind_archive_path: PathBuf,
+    prompt_history_path: PathBuf,
+    /// How many of the popped tasks are already in the archive, as the mind
+    /// gets saved after every command in the interactive modes.
+    archived: Cell<usize>,
+}
+
+impl LocalStorage {
+    /// The lines typed in the interactive mode.
+    pub fn prompt_history_path(&self) -> &Path {
+        &self.prompt_history_path
+    }
 }
 
 impl Storage for LocalStorage {
@@ -33,35 +64,128 @@ impl Storage for LocalStorage {
         };
 
         let reminder_examples_path = local_storage.join("reminder_examples.yml");
-        let mut file = File::create(&reminder_examples_path)?;
-        file.write_all(Reminder::examples().as_bytes())?;
+        write_if_changed(&reminder_examples_path, Reminder::examples())?;
+
+        let config_examples_path = local_storage.join("config_examples.yml");
+        write_if_changed(&config_examples_path, Config::examples())?;
 
         // TODO Add version compatibility logic
         let mind_version_path = local_storage.join("version");
-        let mut file = File::create(&mind_version_path)?;
-        file.write_all(Mind::version().as_bytes())?;
+        write_if_changed(&mind_version_path, Mind::version())?;
+
+        let mind_paused_until_path = local_storage.join("paused_until");
+        let mind_config_path = local_storage.join("config.yml");
+        let mind_history_path = local_storage.join("history.yml");
+        let mind_archive_path = local_storage.join("archive.yml");
+        let prompt_history_path = local_storage.join("prompt_history");
 
         Ok(Self {
             mind_tasks_path,
             mind_reminders_path,
+            mind_paused_until_path,
+            mind_config_path,
+            mind_history_path,
+            mind_archive_path,
+            prompt_history_path,
+            archived: Cell::new(0),
         })
     }
 
     fn load(&self) -> io::Result<Mind> {
-        let mind: Mind = Mind::from(
-            serde_yaml::from_reader(BufReader::new(&File::open(&self.mind_tasks_path)?))
-                .expect("invalid format"),
+        let invalid = |path: &Path, err: String| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), err),
+            )
+        };
+
+        let reminders: Vec<Reminder> =
             serde_yaml::from_reader(BufReader::new(&File::open(&self.mind_reminders_path)?))
-                .expect("invalid format"),
+                .map_err(|err| invalid(&self.mind_reminders_path, err.to_string()))?;
+
+        Reminder::validate_all(&reminders)
+            .map_err(|err| invalid(&self.mind_reminders_path, err))?;
+
+        let mut mind: Mind = Mind::from(
+            serde_yaml::from_reader(BufReader::new(&File::open(&self.mind_tasks_path)?))
+                .map_err(|err| invalid(&self.mind_tasks_path, err.to_string()))?,
+            reminders,
         );
+
+        if self.mind_config_path.exists() {
+            let config: Config =
+                serde_yaml::from_reader(BufReader::new(&File::open(&self.mind_config_path)?))
+                    .map_err(|err| invalid(&self.mind_config_path, err.to_string()))?;
+            config
+                .validate()
+                .map_err(|err| invalid(&self.mind_config_path, err))?;
+            mind.configure(config);
+        }
+
+        if self.mind_paused_until_path.exists() {
+            let paused_until = fs::read_to_string(&self.mind_paused_until_path)?;
+            let paused_until = DateTime::parse_from_rfc3339(paused_until.trim())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            mind.pause(Some(paused_until.with_timezone(&Local)));
+        }
+
         Ok(mind)
     }
 
-    fn save(&self, mind: Mind) -> io::Result<()> {
-        serde_yaml::to_writer(File::create(&self.mind_tasks_path)?, mind.tasks())
-            .expect("failed to save file.");
-        serde_yaml::to_writer(File::create(&self.mind_reminders_path)?, mind.reminders())
-            .expect("failed to save file.");
+    fn save(&self, mind: &Mind) -> io::Result<()> {
+        // The popped tasks are kept in the archive, a list that stays valid
+        // when appended to.
+        let popped = &mind.popped()[self.archived.get()..];
+        if !popped.is_empty() {
+            let popped = serde_yaml::to_string(popped).expect("failed to encode tasks");
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.mind_archive_path)?
+                .write_all(popped.as_bytes())?;
+            self.archived.set(mind.popped().len());
+        }
+
+        write_if_changed(
+            &self.mind_tasks_path,
+            &serde_yaml::to_string(mind.tasks()).expect("failed to save file."),
+        )?;
+        write_if_changed(
+            &self.mind_reminders_path,
+            &serde_yaml::to_string(mind.reminders()).expect("failed to save file."),
+        )?;
+
+        if let Some(paused_until) = mind.paused_until() {
+            write_if_changed(&self.mind_paused_until_path, &paused_until.to_rfc3339())?;
+        } else if self.mind_paused_until_path.exists() {
+            fs::remove_file(&self.mind_paused_until_path)?;
+        }
         Ok(())
     }
+
+    fn record(&self, mind: &Mind) -> io::Result<()> {
+        // The history is a list of samples, appending keeps it a valid list.
+        let sample = serde_yaml::to_string(&[Sample::from(mind)]).expect("failed to encode sample");
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.mind_history_path)?
+            .write_all(sample.as_bytes())
+    }
+
+    fn history(&self) -> io::Result<Vec<Sample>> {
+        if !self.mind_history_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let history: Option<Vec<Sample>> =
+            serde_yaml::from_reader(BufReader::new(&File::open(&self.mind_history_path)?))
+                .map_err(|err| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("{}: {}", self.mind_history_path.display(), err),
+                    )
+                })?;
+        Ok(history.unwrap_or_default())
+    }
 }
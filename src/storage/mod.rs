@@ -1,6 +1,6 @@
 pub mod local;
 
-use crate::Mind;
+use crate::{Mind, Sample};
 use std::io;
 
 pub trait Storage {
@@ -8,5 +8,8 @@ pub trait Storage {
     where
         Self: Sized;
     fn load(&self) -> io::Result<Mind>;
-    fn save(&self, mind: Mind) -> io::Result<()>;
+    fn save(&self, mind: &Mind) -> io::Result<()>;
+    /// Take a sample of the mind for the history, once per run.
+    fn record(&self, mind: &Mind) -> io::Result<()>;
+    fn history(&self) -> io::Result<Vec<Sample>>;
 }
@@ -1,7 +1,96 @@
-use crate::Reminder;
-use chrono::{DateTime, Local};
+use crate::{Reminder, Repeat};
+use chrono::{DateTime, Duration, Local};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::str::FromStr;
+
+/// How much a stale task hurts the productivity.
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum Priority {
+    Someday,
+    Low,
+    #[default]
+    Normal,
+    High,
+    Urgent,
+}
+
+impl Priority {
+    pub fn weight(&self) -> f64 {
+        match self {
+            Self::Someday => 0.25,
+            Self::Low => 0.5,
+            Self::Normal => 1.0,
+            Self::High => 2.0,
+            Self::Urgent => 4.0,
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What a blocked task is waiting on.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Waiting {
+    on: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    follow_up: Option<DateTime<Local>>,
+}
+
+impl Waiting {
+    pub fn new(on: String, follow_up: Option<DateTime<Local>>) -> Self {
+        Self { on, follow_up }
+    }
+
+    /// Who or what the task is waiting on.
+    pub fn on(&self) -> &String {
+        &self.on
+    }
+
+    /// When to follow up, if at all.
+    pub fn follow_up(&self) -> &Option<DateTime<Local>> {
+        &self.follow_up
+    }
+}
+
+/// A focused work session on a task.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Session {
+    start: DateTime<Local>,
+    #[serde(with = "crate::duration")]
+    duration: Duration,
+}
+
+impl Session {
+    pub fn new(start: DateTime<Local>, duration: Duration) -> Self {
+        Self { start, duration }
+    }
+
+    pub fn start(&self) -> &DateTime<Local> {
+        &self.start
+    }
+
+    pub fn duration(&self) -> &Duration {
+        &self.duration
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "someday" => Ok(Self::Someday),
+            "low" => Ok(Self::Low),
+            "normal" => Ok(Self::Normal),
+            "high" => Ok(Self::High),
+            "urgent" => Ok(Self::Urgent),
+            _ => Err(format!("invalid priority: {}", s)),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Task {
@@ -9,6 +98,24 @@ pub struct Task {
     #[serde(skip_serializing_if = "Option::is_none")]
     details: Option<String>,
     start: DateTime<Local>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reminder: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<DateTime<Local>>,
+    #[serde(default, skip_serializing_if = "Priority::is_default")]
+    priority: Priority,
+    #[serde(
+        default,
+        with = "crate::duration::option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    estimate: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    waiting: Option<Waiting>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sessions: Vec<Session>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
 }
 
 impl Task {
@@ -17,7 +124,31 @@ impl Task {
             name,
             details: None,
             start: Local::now(),
+            reminder: None,
+            due: None,
+            priority: Priority::default(),
+            estimate: None,
+            waiting: None,
+            sessions: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// A stable id, derived from the start time.
+    pub fn id(&self) -> String {
+        let mut nanos = self
+            .start
+            .timestamp_nanos_opt()
+            .unwrap_or_else(|| self.start.timestamp()) as u64;
+        let mut id = Vec::new();
+        loop {
+            id.push(std::char::from_digit((nanos % 36) as u32, 36).unwrap());
+            nanos /= 36;
+            if nanos == 0 {
+                break;
+            }
         }
+        id.iter().rev().collect()
     }
 
     pub fn name(&self) -> &String {
@@ -32,31 +163,359 @@ impl Task {
         &self.start
     }
 
+    /// Name of the reminder this task was created from, if any.
+    pub fn reminder(&self) -> &Option<String> {
+        &self.reminder
+    }
+
+    /// When the reminder this task was created from is actually due.
+    pub fn due(&self) -> &Option<DateTime<Local>> {
+        &self.due
+    }
+
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn prioritize(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
+    /// The estimated effort.
+    pub fn estimate(&self) -> &Option<Duration> {
+        &self.estimate
+    }
+
+    pub fn set_estimate(&mut self, estimate: Option<Duration>) {
+        self.estimate = estimate;
+    }
+
+    /// What the task is waiting on, if it's blocked.
+    pub fn waiting(&self) -> &Option<Waiting> {
+        &self.waiting
+    }
+
+    pub fn wait(&mut self, waiting: Option<Waiting>) {
+        self.waiting = waiting;
+    }
+
+    /// The reminder to follow up on the task while it's waiting, linked to
+    /// the task by its id.
+    pub fn follow_up_reminder(&self) -> Option<Reminder> {
+        let waiting = self.waiting.as_ref()?;
+        waiting.follow_up.map(|when| {
+            Reminder::new(
+                format!("Follow up on {}", self.name),
+                Some(format!("Waiting on {}", waiting.on)),
+                when,
+                Repeat::Never,
+            )
+            .following_up(self.id())
+        })
+    }
+
+    /// The focused work sessions. See `mind focus`
+    pub fn sessions(&self) -> &Vec<Session> {
+        &self.sessions
+    }
+
+    pub fn log_session(&mut self, session: Session) {
+        self.sessions.push(session);
+    }
+
+    /// How much the age of the task counts towards the backlog. Each day of
+    /// the estimated effort adds up to the priority weight.
+    pub fn weight(&self) -> f64 {
+        let days = self
+            .estimate
+            .map_or(0.0, |e| e.num_minutes() as f64 / (60.0 * 24.0));
+        self.priority.weight() * (1.0 + days)
+    }
+
+    /// The checkboxes in the details, checked or not.
+    pub fn checklist(&self) -> Vec<bool> {
+        self.details
+            .as_deref()
+            .map(crate::markdown::checklist)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, checked)| checked)
+            .collect()
+    }
+
+    /// The checked and the total checkboxes in the details, if any.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        let checklist = self.checklist();
+        if checklist.is_empty() {
+            None
+        } else {
+            Some((checklist.iter().filter(|c| **c).count(), checklist.len()))
+        }
+    }
+
+    /// The name with the checklist progress, like `Release 3/5`, in at most
+    /// the given columns. Only the name gets ellipsized.
+    pub fn label(&self, width: usize) -> String {
+        match self.progress() {
+            Some((checked, total)) => {
+                let progress = format!(" {}/{}", checked, total);
+                let width = width.saturating_sub(crate::render::width(&progress));
+                format!(
+                    "{}{}",
+                    crate::render::ellipsize(&self.name, width),
+                    progress
+                )
+            }
+            None => crate::render::ellipsize(&self.name, width),
+        }
+    }
+
+    /// Tick the given checkbox in the details, counting from 1, or untick it
+    /// if it's ticked. Returns false if there's no such checkbox.
+    pub fn tick(&mut self, item: usize) -> bool {
+        let details = match &self.details {
+            Some(details) => details,
+            None => return false,
+        };
+        let line = match item
+            .checked_sub(1)
+            .and_then(|i| crate::markdown::checklist(details).get(i).copied())
+        {
+            Some((line, _)) => line,
+            None => return false,
+        };
+
+        let mut lines: Vec<String> = details.lines().map(String::from).collect();
+        lines[line] = crate::markdown::toggle(&lines[line]);
+        self.details = Some(lines.join("\n"));
+        true
+    }
+
+    pub fn tags(&self) -> &Vec<String> {
+        &self.tags
+    }
+
     pub fn edit(&mut self, name: String, details: Option<String>) {
         self.name = name;
         self.details = details;
     }
 
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("name: must not be empty".into());
+        }
+        if self.estimate.is_some_and(|e| e < Duration::zero()) {
+            return Err("estimate: must not be negative".into());
+        }
+        if self
+            .waiting
+            .as_ref()
+            .is_some_and(|w| w.on.trim().is_empty())
+        {
+            return Err("waiting: must be waiting on someone or something".into());
+        }
+        if self.tags.iter().any(|t| t.trim().is_empty()) {
+            return Err("tags: must not be empty".into());
+        }
+        Ok(())
+    }
+
+    /// The task as Markdown, with the details in the body, and the rest in
+    /// the YAML front matter. The unset fields are shown as comments.
+    pub fn to_markdown(&self) -> String {
+        let mut front = serde_yaml::to_value(self).expect("failed to encode task");
+        if let Some(front) = front.as_mapping_mut() {
+            front.remove("details");
+        }
+        let front = serde_yaml::to_string(&front).expect("failed to encode task");
+
+        let hints = [
+            ("due", "# due: 2020-12-25T18:00:00+01:00"),
+            (
+                "priority",
+                "# priority: Normal  # Someday, Low, Normal, High or Urgent",
+            ),
+            ("estimate", "# estimate: 2h"),
+            ("tags", "# tags: [work]"),
+            (
+                "waiting",
+                "# waiting:\n#   on: Bob\n#   follow_up: 2020-12-25T09:00:00+01:00",
+            ),
+        ];
+        let unset: Vec<&str> = hints
+            .iter()
+            .filter(|(key, _)| !front.lines().any(|l| l.starts_with(&format!("{}:", key))))
+            .map(|(_, hint)| *hint)
+            .collect();
+
+        let mut markdown = format!("---\n{}", front);
+        for hint in unset {
+            markdown.push_str(hint);
+            markdown.push('\n');
+        }
+        markdown.push_str("---\n\n");
+        markdown.push_str(self.details.as_deref().unwrap_or_default());
+        markdown
+    }
+
+    /// Read back the task written by `to_markdown`, and validate it.
+    pub fn from_markdown(markdown: &str) -> Result<Self, String> {
+        let rest = markdown
+            .trim_start()
+            .strip_prefix("---\n")
+            .ok_or("missing the front matter, starting with a --- line")?;
+        let (front, details) = match rest.split_once("\n---\n") {
+            Some(parts) => parts,
+            None => rest
+                .strip_suffix("\n---")
+                .map(|front| (front, ""))
+                .ok_or("missing the end of the front matter, a --- line")?,
+        };
+
+        let mut task: serde_yaml::Value =
+            serde_yaml::from_str(front).map_err(|err| err.to_string())?;
+        let details = details.trim();
+        if let Some(task) = task.as_mapping_mut() {
+            if !details.is_empty() {
+                task.insert("details".into(), details.into());
+            }
+        }
+
+        let task: Self = serde_yaml::from_value(task).map_err(|err| err.to_string())?;
+        task.validate()?;
+        Ok(task)
+    }
+
     pub fn from_reminder(reminder: &Reminder) -> Self {
         let mut task = Self::new(format!("📆 {}", &reminder.name().clone()));
         if let Some(details) = reminder.details() {
             task.details = Some(details.clone());
         }
+        task.reminder = Some(reminder.name().clone());
+        task.due = Some(*reminder.when());
         task
     }
 }
 
-impl fmt::Display for Task {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let hr: String = "=".repeat(self.name.chars().count());
+impl Task {
+    /// The name, the meta data and the details rendered from Markdown, in
+    /// lines of at most the given columns, with the terminal styles if styled.
+    pub fn render(&self, width: usize, styled: bool) -> String {
+        let mut f = String::new();
+        self.write(&mut f, width, styled)
+            .expect("failed to render the task");
+        f
+    }
 
-        let details = self
-            .details
-            .clone()
-            .unwrap_or_else(|| "No details...".into());
+    fn write<W: fmt::Write>(&self, f: &mut W, width: usize, styled: bool) -> fmt::Result {
+        let hr: String = "=".repeat(crate::render::width(&self.name));
 
         writeln!(f, "{}", &self.name)?;
         writeln!(f, "{}", hr)?;
-        write!(f, "{}", details)
+
+        if let Some(waiting) = &self.waiting {
+            write!(f, "Waiting on: {}", waiting.on)?;
+            if let Some(follow_up) = waiting.follow_up {
+                write!(f, " | Follow up: {}", follow_up.to_rfc2822())?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.sessions.is_empty() {
+            let focused = self
+                .sessions
+                .iter()
+                .fold(Duration::zero(), |x, s| x + s.duration);
+            writeln!(
+                f,
+                "Focused: {} in {} session(s)",
+                crate::duration::format(&focused),
+                self.sessions.len()
+            )?;
+        }
+
+        if !self.tags.is_empty() {
+            writeln!(f, "Tags: {}", self.tags.join(", "))?;
+        }
+
+        if !self.priority.is_default() || self.estimate.is_some() {
+            write!(f, "Priority: {:?}", self.priority)?;
+            if let Some(estimate) = self.estimate {
+                write!(f, " | Estimate: {}", crate::duration::format(&estimate))?;
+            }
+            writeln!(f, " | Weight: {}", self.weight())?;
+            writeln!(f)?;
+        }
+
+        match &self.details {
+            Some(details) => write!(
+                f,
+                "{}",
+                crate::markdown::render(details, width, styled).join("\n")
+            ),
+            None => write!(f, "No details..."),
+        }
+    }
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write(f, crate::render::terminal_width(), false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(task: &Task) -> String {
+        serde_yaml::to_string(task).unwrap()
+    }
+
+    #[test]
+    fn markdown_round_trip() {
+        let full: Task = serde_yaml::from_str(
+            r##"
+name: "Write the release notes: v0.8"
+details: "# Notes\n\n- [ ] features\n\n---\n\nSee the changelog."
+start: 2020-07-10T08:00:00+02:00
+reminder: Release
+due: 2020-07-17T18:00:00+02:00
+priority: High
+estimate: 1h 30m
+waiting:
+  on: Bob
+  follow_up: 2020-07-13T09:00:00+02:00
+sessions:
+  - start: 2020-07-10T10:00:00+02:00
+    duration: 25m
+tags: [work, docs]
+"##,
+        )
+        .unwrap();
+        let bare = Task::new("Nothing else".into());
+
+        for task in [full, bare].iter() {
+            let markdown = task.to_markdown();
+            assert_eq!(yaml(&Task::from_markdown(&markdown).unwrap()), yaml(task));
+        }
+    }
+
+    #[test]
+    fn markdown_hints() {
+        let markdown = Task::new("Nothing else".into()).to_markdown();
+        assert!(markdown.contains("\n# estimate: 2h\n"));
+
+        // Uncommenting the hints sets the fields.
+        let markdown = markdown
+            .replace("# estimate: 2h", "estimate: 2h")
+            .replace("# tags: [work]", "tags: [work]");
+        let task = Task::from_markdown(&format!("{}Some details\n", markdown)).unwrap();
+        assert_eq!(*task.estimate(), Some(Duration::hours(2)));
+        assert_eq!(*task.tags(), vec!["work".to_string()]);
+        assert_eq!(*task.details(), Some("Some details".into()));
+
+        assert!(Task::from_markdown("name: no front matter").is_err());
+        assert!(Task::from_markdown("---\nname: \" \"\n---\n").is_err());
     }
 }
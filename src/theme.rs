@@ -0,0 +1,233 @@
+use crate::Productivity;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::env;
+use std::str::FromStr;
+
+/// A color, written as `"#rrggbb"` in the config.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+
+    /// Darken the color by the given shade, 255 keeps it as is.
+    pub fn dim(&self, shade: u8) -> Self {
+        let dim = |c: u8| (c as u16 * shade as u16 / 255) as u8;
+        Self(dim(self.0), dim(self.1), dim(self.2))
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii())
+            .ok_or_else(|| format!("invalid color: {}, expected #rrggbb", s))?;
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16)
+                .map_err(|_| format!("invalid color: {}, expected #rrggbb", s))
+        };
+        Ok(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl Serialize for Rgb {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Rgb {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl From<(u8, u8, u8)> for Rgb {
+    fn from((r, g, b): (u8, u8, u8)) -> Self {
+        Self(r, g, b)
+    }
+}
+
+/// The colors of the output. See `theme` in ~/.mind/config.yml
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Theme {
+    optimal: Rgb,
+    high: Rgb,
+    normal: Rgb,
+    low: Rgb,
+    unproductive: Rgb,
+    /// The ages, and the tasks waiting on others.
+    age: Rgb,
+    /// The positions of the tasks, uncolored unless set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<Rgb>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            optimal: Productivity::Optimal.rgb().into(),
+            high: Productivity::High.rgb().into(),
+            normal: Productivity::Normal.rgb().into(),
+            low: Productivity::Low.rgb().into(),
+            unproductive: Productivity::UnProductive.rgb().into(),
+            age: Rgb(170, 170, 170),
+            index: None,
+        }
+    }
+}
+
+impl Theme {
+    /// The color of the tasks at the given productivity level.
+    pub fn productivity(&self, productivity: Productivity) -> Rgb {
+        match productivity {
+            Productivity::Optimal => self.optimal,
+            Productivity::High => self.high,
+            Productivity::Normal => self.normal,
+            Productivity::Low => self.low,
+            Productivity::UnProductive => self.unproductive,
+        }
+    }
+
+    pub fn age(&self) -> Rgb {
+        self.age
+    }
+
+    pub fn index(&self) -> Option<Rgb> {
+        self.index
+    }
+}
+
+/// When to color the output. See `--color`.
+#[derive(Copy, Clone, PartialEq, Default)]
+pub enum ColorMode {
+    /// Only on a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            _ => Err(format!(
+                "invalid color mode: {}, expected auto, always or never",
+                s
+            )),
+        }
+    }
+}
+
+/// The colors the terminal can show.
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub enum Colors {
+    #[default]
+    None,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl Colors {
+    /// The colors to use in the given mode, for a terminal output or not.
+    pub fn detect(mode: ColorMode, terminal: bool) -> Self {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        match mode {
+            ColorMode::Never => Self::None,
+            ColorMode::Auto if no_color || !terminal => Self::None,
+            _ => Self::supported(),
+        }
+    }
+
+    /// The colors supported by the terminal, as told by `COLORTERM` and `TERM`.
+    fn supported() -> Self {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            Self::TrueColor
+        } else if term.contains("256color") {
+            Self::Ansi256
+        } else if term == "dumb" {
+            Self::None
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        *self != Self::None
+    }
+
+    /// The escape code to write in the given color, or the closest one.
+    pub fn fg(&self, rgb: Rgb) -> String {
+        let Rgb(r, g, b) = rgb;
+        match self {
+            Self::None => String::new(),
+            Self::TrueColor => format!("\x1b[38;2;{};{};{}m", r, g, b),
+            Self::Ansi256 => {
+                // The 6x6x6 color cube of the 256 colors.
+                let level = |c: u8| (c as u16 * 5 + 127) / 255;
+                format!(
+                    "\x1b[38;5;{}m",
+                    16 + 36 * level(r) + 6 * level(g) + level(b)
+                )
+            }
+            Self::Ansi16 => {
+                // The channels at least half as bright as the brightest one
+                // make the color, and the bright ones get the bright variant.
+                let max = r.max(g).max(b);
+                if max < 64 {
+                    return "\x1b[30m".into();
+                }
+                let on = |c: u8| (c >= max / 2) as u8;
+                let code = on(r) + 2 * on(g) + 4 * on(b);
+                format!("\x1b[{}m", if max > 200 { 90 } else { 30 } + code)
+            }
+        }
+    }
+
+    /// The escape code to get back to the default color.
+    pub fn reset(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            _ => "\x1b[39m",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fallbacks() {
+        let green = Rgb(0, 200, 0);
+        assert_eq!(Colors::None.fg(green), "");
+        assert_eq!(Colors::TrueColor.fg(green), "\x1b[38;2;0;200;0m");
+        assert_eq!(Colors::Ansi256.fg(green), "\x1b[38;5;40m");
+        assert_eq!(Colors::Ansi16.fg(green), "\x1b[32m");
+        assert_eq!(Colors::Ansi16.fg(Rgb(255, 0, 0)), "\x1b[91m");
+    }
+
+    #[test]
+    fn parse_hex() {
+        assert_eq!("#00c800".parse::<Rgb>(), Ok(Rgb(0, 200, 0)));
+        assert!("00c800".parse::<Rgb>().is_err());
+        assert!("#00c8".parse::<Rgb>().is_err());
+    }
+}
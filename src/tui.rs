@@ -0,0 +1,308 @@
+//! The full screen interactive mode. Run `mind tui`
+
+use crate::{render, Command, Mind};
+use chrono::Local;
+use chrono_humanize::HumanTime;
+use std::io::{self, Write};
+use std::sync::mpsc;
+use std::thread;
+use std::time;
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{ToAlternateScreen, ToMainScreen};
+use termion::{clear, cursor, style, terminal_size};
+
+static HELP: &str =
+    "↑↓ select  ⏎ continue  p pop  e edit  r remind  g details  tab reminders  n new  q quit";
+
+#[derive(PartialEq)]
+enum Pane {
+    Details,
+    Reminders,
+    Hidden,
+}
+
+/// What's next after a key.
+enum Step {
+    Stay,
+    /// The mind changed, and needs to be saved.
+    Changed,
+    Quit,
+}
+
+struct Tui {
+    selected: usize,
+    pane: Pane,
+    input: Option<String>,
+}
+
+/// Read the keys in the background, one at a time. The next key is read only
+/// after the previous one is acknowledged, so that the editors opened in
+/// between get all the input, and no key is read once the sender is dropped.
+pub fn keys() -> (mpsc::Receiver<io::Result<Key>>, mpsc::Sender<()>) {
+    let (key_tx, key_rx) = mpsc::channel();
+    let (ack_tx, ack_rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        for key in io::stdin().keys() {
+            if key_tx.send(key).is_err() || ack_rx.recv().is_err() {
+                return;
+            }
+        }
+    });
+
+    (key_rx, ack_tx)
+}
+
+/// Run the full screen interactive mode, until the user quits. The mind gets
+/// saved after every change.
+pub fn run<F>(mind: &mut Mind, mut save: F) -> io::Result<()>
+where
+    F: FnMut(&Mind) -> io::Result<()>,
+{
+    if !atty::is(atty::Stream::Stdout) {
+        return Err(io::Error::other("tui needs a terminal"));
+    }
+
+    let mut stdout = io::stdout().into_raw_mode()?;
+    write!(stdout, "{}{}", ToAlternateScreen, cursor::Hide)?;
+
+    let (keys, ack) = keys();
+    let mut tui = Tui {
+        selected: mind.tasks().len().saturating_sub(1),
+        pane: Pane::Details,
+        input: None,
+    };
+
+    loop {
+        tui.selected = tui.selected.min(mind.tasks().len().saturating_sub(1));
+        draw(&mut stdout, mind, &tui)?;
+
+        // Re-render every second to keep the ages and the colors fresh.
+        let key = match keys.recv_timeout(time::Duration::from_secs(1)) {
+            Ok(key) => key?,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+
+        let step = handle(&mut stdout, mind, &mut tui, key)?;
+        ack.send(()).ok();
+        match step {
+            Step::Stay => {}
+            Step::Changed => save(mind)?,
+            Step::Quit => break,
+        }
+    }
+
+    write!(stdout, "{}{}", cursor::Show, ToMainScreen)?;
+    stdout.flush()
+}
+
+/// Handle the key, and tell what's next.
+fn handle(
+    stdout: &mut RawTerminal<io::Stdout>,
+    mind: &mut Mind,
+    tui: &mut Tui,
+    key: Key,
+) -> io::Result<Step> {
+    if let Some(input) = tui.input.as_mut() {
+        match key {
+            Key::Char('\n') => {
+                let name = input.trim().to_string();
+                tui.input = None;
+                if !name.is_empty() {
+                    mind.act(Command::Push(name));
+                    tui.selected = mind.tasks().len() - 1;
+                    return Ok(Step::Changed);
+                }
+            }
+            Key::Esc | Key::Ctrl('c') => tui.input = None,
+            Key::Backspace => {
+                input.pop();
+            }
+            Key::Char(c) => input.push(c),
+            _ => {}
+        }
+        return Ok(Step::Stay);
+    }
+
+    let len = mind.tasks().len();
+    let index = tui.selected;
+
+    match key {
+        Key::Char('q') | Key::Esc | Key::Ctrl('c') => return Ok(Step::Quit),
+        Key::Up | Key::Char('k') => tui.selected = index.saturating_sub(1),
+        Key::Down | Key::Char('j') => tui.selected = (index + 1).min(len.saturating_sub(1)),
+        Key::Home => tui.selected = 0,
+        Key::End => tui.selected = len.saturating_sub(1),
+        Key::Char('\n') | Key::Char('c') if len > 0 => {
+            mind.act(Command::Continue(index));
+            tui.selected = len - 1;
+            return Ok(Step::Changed);
+        }
+        Key::Char('p') if len > 0 => {
+            mind.act(Command::Pop(index));
+            return Ok(Step::Changed);
+        }
+        Key::Char('e') if len > 0 => {
+            with_main_screen(stdout, || mind.act(Command::Edit(index)))?;
+            return Ok(Step::Changed);
+        }
+        Key::Char('r') if len > 0 => {
+            with_main_screen(stdout, || mind.act(Command::Remind(index)))?;
+            return Ok(Step::Changed);
+        }
+        Key::Char('g') => {
+            tui.pane = match tui.pane {
+                Pane::Details => Pane::Hidden,
+                _ => Pane::Details,
+            }
+        }
+        Key::Char('\t') => {
+            tui.pane = match tui.pane {
+                Pane::Reminders => Pane::Details,
+                _ => Pane::Reminders,
+            }
+        }
+        Key::Char('n') | Key::Char('a') => tui.input = Some(String::new()),
+        _ => {}
+    }
+    Ok(Step::Stay)
+}
+
+/// Leave the full screen for the editor, and come back.
+fn with_main_screen<F>(stdout: &mut RawTerminal<io::Stdout>, f: F) -> io::Result<()>
+where
+    F: FnOnce(),
+{
+    write!(stdout, "{}{}", cursor::Show, ToMainScreen)?;
+    stdout.flush()?;
+    stdout.suspend_raw_mode()?;
+
+    f();
+
+    stdout.activate_raw_mode()?;
+    write!(stdout, "{}{}", ToAlternateScreen, cursor::Hide)?;
+    stdout.flush()
+}
+
+fn draw<W: Write>(out: &mut W, mind: &Mind, tui: &Tui) -> io::Result<()> {
+    let (width, height) = terminal_size().unwrap_or((80, 24));
+    let (width, height) = (width as usize, height as usize);
+    let now = Local::now();
+    let productivity = mind.productivity();
+    let colors = mind.colors();
+    let theme = mind.config().theme();
+    let productivity_color = colors.fg(theme.productivity(productivity));
+    let age_color = colors.fg(theme.age());
+
+    let mut lines: Vec<String> = Vec::new();
+    lines.push(format!(
+        "{}{}mind{} · {} tasks · {}{:?}{}",
+        style::Bold,
+        productivity_color,
+        style::Reset,
+        mind.tasks().len(),
+        productivity_color,
+        productivity,
+        colors.reset(),
+    ));
+    lines.push(String::new());
+
+    // The tasks take the upper half, or all of the screen without a pane.
+    let list_height = match tui.pane {
+        Pane::Hidden => height.saturating_sub(4),
+        _ => (height.saturating_sub(5) / 2).max(1),
+    };
+    let first = (tui.selected + 1).saturating_sub(list_height);
+    let idx_width = mind.tasks().len().to_string().len();
+
+    for (idx, task) in mind
+        .tasks()
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(list_height)
+    {
+        let age = HumanTime::from(-mind.age(task, now)).to_string();
+        let name_width = width.saturating_sub(idx_width + render::width(&age) + 5);
+        let name = render::pad(&task.label(name_width), name_width);
+        let highlight = if idx == tui.selected {
+            style::Invert.to_string()
+        } else {
+            String::new()
+        };
+        let name_color = if task.waiting().is_some() {
+            &age_color
+        } else {
+            &productivity_color
+        };
+
+        lines.push(format!(
+            "{}[{:idx_width$}] {}{} {}{}{}",
+            highlight,
+            idx,
+            name_color,
+            name,
+            age_color,
+            age,
+            style::Reset,
+            idx_width = idx_width,
+        ));
+    }
+
+    while lines.len() < list_height + 2 {
+        lines.push(String::new());
+    }
+
+    match tui.pane {
+        Pane::Details => {
+            lines.push("─".repeat(width));
+            if let Some(task) = mind.tasks().get(tui.selected) {
+                lines.extend(
+                    task.to_string()
+                        .lines()
+                        .map(|l| render::ellipsize(l, width)),
+                );
+            }
+        }
+        Pane::Reminders => {
+            lines.push("─".repeat(width));
+            lines.extend(
+                crate::output::reminders(mind, &crate::output::Format::Human)
+                    .lines()
+                    .map(|l| render::ellipsize(l, width)),
+            );
+        }
+        Pane::Hidden => {}
+    }
+
+    lines.truncate(height.saturating_sub(1));
+    while lines.len() < height.saturating_sub(1) {
+        lines.push(String::new());
+    }
+
+    let footer = match &tui.input {
+        Some(input) => format!("New task: {}", input),
+        None => HELP.into(),
+    };
+    lines.push(format!(
+        "{}{}{}",
+        style::Faint,
+        render::ellipsize(&footer, width),
+        style::Reset
+    ));
+
+    write!(out, "{}", cursor::Goto(1, 1))?;
+    for (row, line) in lines.iter().enumerate() {
+        write!(
+            out,
+            "{}{}{}",
+            cursor::Goto(1, row as u16 + 1),
+            clear::CurrentLine,
+            line
+        )?;
+    }
+    out.flush()
+}